@@ -0,0 +1,181 @@
+use csv_file::records;
+use date::Date;
+use error::HomeCalcError;
+use solver::bisect;
+use std::fs;
+use std::path::Path;
+
+/// Number of days in a year for dated cash flows
+const DAYS_BY_YEAR: f64 = 365_f64;
+/// Rates between which a sign change of the present value is looked for
+const RATE_BRACKETS: [f64; 16] = [
+    -0.99, -0.5, -0.2, -0.05, -0.01, 0_f64, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1_f64, 2_f64, 5_f64,
+    10_f64,
+];
+
+/// Return the net present value of periodic cash flows
+///
+/// The first flow is at period 0, inflows are positive and outflows negative.
+///
+/// # Arguments
+/// * `rate` - the discount rate by period
+/// * `flows` - the cash flow of each period
+///
+/// # Example
+/// ```
+/// // Paying 100 now to get 110 in a period is worth 0 at 10%
+/// let value = npv(0.1, &[-100_f64, 110_f64]);
+/// ```
+pub fn npv(rate: f64, flows: &[f64]) -> f64 {
+    flows
+        .iter()
+        .enumerate()
+        .fold(0_f64, |value, (period, flow)| {
+            value + flow / (1_f64 + rate).powi(period as i32)
+        })
+}
+
+/// Return the internal rate of return by period of periodic cash flows
+///
+/// # Arguments
+/// * `flows` - the cash flow of each period, the first one at period 0
+pub fn irr(flows: &[f64]) -> Result<f64, HomeCalcError> {
+    find_rate(|rate| npv(rate, flows), flows.iter().cloned(), "IRR")
+}
+
+/// Return the rate by year equivalent to a rate by period
+///
+/// # Arguments
+/// * `rate` - the rate by period
+/// * `periodicity` - number of periods by year
+pub fn yearly_rate(rate: f64, periodicity: u8) -> f64 {
+    (1_f64 + rate).powi(periodicity as i32) - 1_f64
+}
+
+/// Return the net present value of dated cash flows, discounted to the first date
+///
+/// # Arguments
+/// * `rate` - the discount rate by year
+/// * `flows` - the dates and amounts of the cash flows
+pub fn xnpv(rate: f64, flows: &[(Date, f64)]) -> f64 {
+    let start = match flows.iter().map(|&(date, _)| date).min() {
+        Some(start) => start,
+        None => return 0_f64,
+    };
+    flows.iter().fold(0_f64, |value, &(date, flow)| {
+        let years = start.days_until(&date) as f64 / DAYS_BY_YEAR;
+        value + flow / (1_f64 + rate).powf(years)
+    })
+}
+
+/// Return the internal rate of return by year of dated cash flows
+///
+/// # Arguments
+/// * `flows` - the dates and amounts of the cash flows, in any order
+///
+/// # Example
+/// ```
+/// let flows = vec![
+///     (Date::new(2020, 1, 1).unwrap(), -1000_f64),
+///     (Date::new(2021, 1, 1).unwrap(), 1050_f64),
+/// ];
+/// let rate = xirr(&flows)?;
+/// ```
+pub fn xirr(flows: &[(Date, f64)]) -> Result<f64, HomeCalcError> {
+    find_rate(
+        |rate| xnpv(rate, flows),
+        flows.iter().map(|&(_, flow)| flow),
+        "XIRR",
+    )
+}
+
+/// Find the rate cancelling a present value
+///
+/// The flows must contain both inflows and outflows. The rates of `RATE_BRACKETS` are scanned
+/// for a sign change of the present value, which is then narrowed by bisection.
+fn find_rate<F, I>(present_value: F, flows: I, name: &'static str) -> Result<f64, HomeCalcError>
+where
+    F: Fn(f64) -> f64,
+    I: Iterator<Item = f64>,
+{
+    let (inflow, outflow) = flows.fold((false, false), |(inflow, outflow), flow| {
+        (inflow || flow > 0_f64, outflow || flow < 0_f64)
+    });
+    if !inflow || !outflow {
+        return Err(HomeCalcError::NoSolution(name));
+    }
+    let brackets = &RATE_BRACKETS;
+    let values: Vec<f64> = brackets.iter().map(|&rate| present_value(rate)).collect();
+    // Look for the sign change the closest to 0%
+    let mut candidates: Vec<usize> = (0..brackets.len() - 1)
+        .filter(|&index| values[index].signum() != values[index + 1].signum())
+        .collect();
+    candidates.sort_by(|&a, &b| {
+        let distance = |index: usize| brackets[index].abs().min(brackets[index + 1].abs());
+        distance(a).partial_cmp(&distance(b)).unwrap()
+    });
+    candidates
+        .into_iter()
+        .filter_map(|index| bisect(&present_value, brackets[index], brackets[index + 1], 1e-10))
+        .next()
+        .ok_or(HomeCalcError::NoSolution(name))
+}
+
+/// Load dated cash flows from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_flows(path: &Path) -> Result<Vec<(Date, f64)>, HomeCalcError> {
+    parse_flows(&fs::read_to_string(path)?)
+}
+
+/// Parse dated cash flows from CSV lines of `date;amount`, inflows being positive
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_flows(content: &str) -> Result<Vec<(Date, f64)>, HomeCalcError> {
+    let mut flows = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let amount = record
+            .number(1)
+            .ok_or_else(|| record.error("invalid amount"))?;
+        flows.push((date, amount));
+    }
+    Ok(flows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_npv_and_irr() {
+        assert!(npv(0.1, &[-100_f64, 110_f64]).abs() < 1e-9);
+        assert!((npv(0_f64, &[-100_f64, 60_f64, 60_f64]) - 20_f64).abs() < 1e-9);
+        let rate = irr(&[-1000_f64, 300_f64, 400_f64, 500_f64]).unwrap();
+        assert!((rate - 0.088963).abs() < 1e-6);
+        let rate = irr(&[-1000_f64, 500_f64, 400_f64]).unwrap();
+        assert!(npv(rate, &[-1000_f64, 500_f64, 400_f64]).abs() < 1e-6);
+        assert!(rate < 0_f64);
+        assert!(irr(&[100_f64, 200_f64]).is_err());
+        assert!((yearly_rate(0.01, 12) - 0.126825).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_xirr() {
+        // Reference value of the usual spreadsheet example: 37.34%
+        let flows = parse_flows(
+            "date;amount\n2008-01-01;-10000\n2008-03-01;2750\n2008-10-30;4250\n\
+             2009-02-15;3250\n2009-04-01;2750\n",
+        ).unwrap();
+        let rate = xirr(&flows).unwrap();
+        assert!((rate - 0.373363).abs() < 1e-5, "{}", rate);
+        assert!(xnpv(rate, &flows).abs() < 1e-6);
+        assert!(xirr(&flows[..1]).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 use super::{common_home_args, parse_common_home_args};
+use cash_flow::{irr, yearly_rate};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::inflation::parse_money;
+use cli::{or_none, parse_value};
 use comparators;
+use error::HomeCalcError;
 
 pub const SUB_HOME_COMPARE_AT: &str = "compare-at";
 const ARG_PERIOD: &str = "n-periods";
@@ -21,14 +25,12 @@ pub fn home_compare_at_subcommand<'a, 'b>() -> App<'a, 'b> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the parameters
-pub fn execute_home_compare_at<'a>(matches: &ArgMatches<'a>) {
-    let home_invest = parse_common_home_args(matches);
-    let at = matches
-        .value_of(ARG_PERIOD)
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
-    let (purchase, invest) = home_invest.capital_at(at);
+pub fn execute_home_compare_at<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let home_invest = parse_common_home_args(matches)?;
+    let at = parse_value(matches, ARG_PERIOD)?;
+    let money = parse_money(matches, comparators::PERIODICITY)?;
+    let (purchase, invest) = home_invest.capital_at(at)?;
+    let (purchase, invest) = (money.value(purchase, at), money.value(invest, at));
     println!(
         "*** For a supply of {}, a loan of {} on {} years with a rate of {}%, \
          purchase charges of {}%, annual charges of {}% and an home appreciation of {}% by year",
@@ -45,14 +47,68 @@ pub fn execute_home_compare_at<'a>(matches: &ArgMatches<'a>) {
         home_invest.rent,
         home_invest.invest_rate * 100_f32
     );
+    for cost in &home_invest.owner_costs {
+        println!("*** The owner also pays the {}", cost);
+    }
+    if let Some(ref price_path) = home_invest.price_path {
+        let rates: Vec<String> = price_path
+            .rates
+            .iter()
+            .map(|rate| format!("{:.2}%", rate * 100_f32))
+            .collect();
+        println!(
+            "*** The home price varies by {} in the next years, the last variation being kept",
+            rates.join(", ")
+        );
+    }
+    if let Some(ref indexation) = home_invest.rent_plan.indexation {
+        match indexation.rates.len() {
+            1 => println!(
+                "*** The rent is revised by {}% every year",
+                indexation.rates[0] * 100_f32
+            ),
+            years => println!("*** The rent follows the index over {} years", years),
+        }
+    }
+    for &(from, rent) in &home_invest.rent_plan.changes {
+        println!("*** The rent becomes {} from period {}", rent, from);
+    }
+    for loan in &home_invest.other_loans {
+        println!(
+            "*** The purchase is also financed by a loan of {} on {} years with a rate of {}%",
+            loan.capital,
+            loan.years,
+            loan.interest_rate_year * 100_f32
+        );
+    }
+    if let Some(wrapper) = home_invest.wrapper {
+        println!("*** The investment is held in a {} and compared after tax", wrapper);
+    }
+    if let Some(ref portfolio) = home_invest.portfolio {
+        println!("*** The investment is spread over a portfolio: {}", portfolio);
+    }
+    money.print_assumption();
     let years_round = format!("{}", at / comparators::PERIODICITY as u32);
     let mut table = table!(["title", "at (periods)", "at (~years)", "value"]);
     table.add_row(row![
         "term price for home purchase",
         "NONE",
         "NONE",
-        format!("{:.02}", home_invest.loan_term_price())
+        format!("{:.02}", money.value(home_invest.loan_term_price()?, at))
     ]);
+    for cost in &home_invest.owner_costs {
+        let paid = money.total(
+            home_invest.owner_cost_total(cost, at),
+            |period| Ok(home_invest.owner_cost_at(cost, period)),
+            at,
+        )?;
+        table.add_row(row![
+            format!("{} paid", cost.kind),
+            at,
+            years_round,
+            format!("{:.02}", paid)
+        ]);
+    }
     table.add_row(row![
         "capital for home purchase",
         at,
@@ -71,5 +127,14 @@ pub fn execute_home_compare_at<'a>(matches: &ArgMatches<'a>) {
         years_round,
         format!("{:.02}", purchase - invest)
     ]);
+    let rate = irr(&home_invest.cash_flows(at)?)
+        .map(|rate| yearly_rate(rate, comparators::PERIODICITY) * 100_f64);
+    table.add_row(row![
+        "return of the purchase (% by year)",
+        at,
+        years_round,
+        or_none(rate.map(|rate| format!("{:.02}", rate)))?
+    ]);
     table.printstd();
+    Ok(())
 }
@@ -1,8 +1,24 @@
 mod compare_at;
+mod plan;
+mod stress;
 
 use self::compare_at::{execute_home_compare_at, home_compare_at_subcommand, SUB_HOME_COMPARE_AT};
+use self::plan::{execute_home_plan, home_plan_subcommand, SUB_HOME_PLAN};
+use self::stress::{execute_home_stress, home_stress_subcommand, SUB_HOME_STRESS};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::invest::{asset_arg, parse_portfolio, parse_wrapper, rebalancing_arg, wrapper_arg};
+use cli::{parse_optional_value, parse_pairs, parse_percent, parse_value};
 use comparators::HomeInvest;
+use date::Date;
+use error::HomeCalcError;
+use home_price::Crash;
+use home_savings::HomeSavingsPlan;
+use loan::Loan;
+use owner_costs::OwnerCost;
+use inflation::{load_index, Inflation};
+use regulated::RegulatedAccount;
+use rent::RentPlan;
+use std::path::Path;
 
 pub const SUB_HOME: &str = "home";
 const ARG_SUPPLY: &str = "supply";
@@ -14,10 +30,22 @@ const ARG_HOME_APPRECIATION: &str = "home-appreciation";
 const ARG_HOME_RENT: &str = "rent";
 const ARG_INVEST_RATE_RENT: &str = "invest-rate";
 const ARG_YEARS: &str = "years";
+const ARG_SAVINGS_LOAN: &str = "savings-loan";
+const ARG_RENT_INDEXATION: &str = "rent-indexation";
+const ARG_RENT_INDEX_FILE: &str = "rent-index-file";
+const ARG_RENT_CHANGE: &str = "rent-change";
+const ARG_PRICE_INDEX_FILE: &str = "price-index-file";
+const ARG_PRICE_CRASH: &str = "price-crash";
+const ARG_AREA: &str = "area";
+const ARG_OWNER_COST: &str = "owner-cost";
 
 /// Returns the home sub commands
 pub fn home_sub_commands<'a, 'b>() -> Vec<App<'a, 'b>> {
-    let home_sub_commands = vec![home_compare_at_subcommand()];
+    let home_sub_commands = vec![
+        home_compare_at_subcommand(),
+        home_stress_subcommand(),
+        home_plan_subcommand(),
+    ];
     let sub_commands = vec![SubCommand::with_name(SUB_HOME).subcommands(home_sub_commands)];
     sub_commands
 }
@@ -26,10 +54,15 @@ pub fn home_sub_commands<'a, 'b>() -> Vec<App<'a, 'b>> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the parameters
-pub fn execute_home_sub_command<'a>(matches: &ArgMatches<'a>) {
+pub fn execute_home_sub_command<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
     match matches.subcommand() {
         (SUB_HOME_COMPARE_AT, Some(matches)) => execute_home_compare_at(matches),
-        _ => println!("*** No command found"),
+        (SUB_HOME_STRESS, Some(matches)) => execute_home_stress(matches),
+        (SUB_HOME_PLAN, Some(matches)) => execute_home_plan(matches),
+        _ => {
+            println!("*** No command found");
+            Ok(())
+        }
     }
 }
 
@@ -66,18 +99,75 @@ pub fn common_home_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .takes_value(true)
             .required(true)
             .help("the annual charges"),
+        Arg::with_name(ARG_AREA)
+            .long(ARG_AREA)
+            .short("S")
+            .takes_value(true)
+            .required(false)
+            .help("the area of the home in square meters"),
+        Arg::with_name(ARG_OWNER_COST)
+            .long(ARG_OWNER_COST)
+            .short("o")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help(
+                "a cost of the owner on top of the annual charges: kind:amount:base[:indexation], \
+                 the kind being property-tax, co-ownership, insurance, maintenance or \
+                 large-works and the base fixed, value (percent) or m2, e.g. \
+                 property-tax:1500:fixed:2",
+            ),
         Arg::with_name(ARG_HOME_APPRECIATION)
             .long(ARG_HOME_APPRECIATION)
             .short("e")
             .takes_value(true)
             .required(true)
             .help("the home appreciation by year"),
+        Arg::with_name(ARG_PRICE_INDEX_FILE)
+            .long(ARG_PRICE_INDEX_FILE)
+            .short("I")
+            .takes_value(true)
+            .required(false)
+            .conflicts_with(ARG_PRICE_CRASH)
+            .help("the home price index from a CSV file (date;value), replacing the appreciation"),
+        Arg::with_name(ARG_PRICE_CRASH)
+            .long(ARG_PRICE_CRASH)
+            .short("K")
+            .takes_value(true)
+            .required(false)
+            .allow_hyphen_values(true)
+            .help(
+                "a crash of the home price then a recovery toward the appreciation: \
+                 year:drop:recovery-years, e.g. 3:-20:5",
+            ),
         Arg::with_name(ARG_HOME_RENT)
             .long(ARG_HOME_RENT)
             .short("m")
             .takes_value(true)
             .required(true)
             .help("the home rent for a month"),
+        Arg::with_name(ARG_RENT_INDEXATION)
+            .long(ARG_RENT_INDEXATION)
+            .short("x")
+            .takes_value(true)
+            .required(false)
+            .conflicts_with(ARG_RENT_INDEX_FILE)
+            .help("the yearly revision of the rent in percent"),
+        Arg::with_name(ARG_RENT_INDEX_FILE)
+            .long(ARG_RENT_INDEX_FILE)
+            .short("X")
+            .takes_value(true)
+            .required(false)
+            .help("the rent index from a CSV file (date;value), the rent following its variations"),
+        Arg::with_name(ARG_RENT_CHANGE)
+            .long(ARG_RENT_CHANGE)
+            .short("c")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help("new rent from a period when moving (period:rent)"),
         Arg::with_name(ARG_INVEST_RATE_RENT)
             .long(ARG_INVEST_RATE_RENT)
             .short("i")
@@ -90,6 +180,20 @@ pub fn common_home_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .takes_value(true)
             .required(true)
             .help("the years for the purchase"),
+        Arg::with_name(ARG_SAVINGS_LOAN)
+            .long(ARG_SAVINGS_LOAN)
+            .short("P")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help(
+                "loan of a home savings plan added to the main loan: \
+                 kind:opening:balance:deposit:months:years, e.g. pel:2019-03-01:1000:200:60:10",
+            ),
+        wrapper_arg(),
+        asset_arg(),
+        rebalancing_arg(),
     ]
 }
 
@@ -97,49 +201,84 @@ pub fn common_home_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
 ///
 /// # Arguments
 /// *  `matches` - cli arguments matches
-pub fn parse_common_home_args<'a>(matches: &ArgMatches<'a>) -> HomeInvest {
-    HomeInvest::new(
-        matches
-            .value_of(ARG_SUPPLY)
-            .unwrap()
-            .parse::<u32>()
-            .unwrap(),
-        matches.value_of(ARG_LOAN).unwrap().parse::<u32>().unwrap(),
-        matches
-            .value_of(ARG_LOAN_RATE)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_PURCHASE_CHARGES)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_ANNUAL_CHARGES)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_HOME_APPRECIATION)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_HOME_RENT)
-            .unwrap()
-            .parse::<u32>()
-            .unwrap(),
-        matches
-            .value_of(ARG_INVEST_RATE_RENT)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches.value_of(ARG_YEARS).unwrap().parse::<u8>().unwrap(),
-    )
+pub fn parse_common_home_args<'a>(matches: &ArgMatches<'a>) -> Result<HomeInvest, HomeCalcError> {
+    let mut home_invest = HomeInvest::new(
+        parse_value(matches, ARG_SUPPLY)?,
+        parse_value(matches, ARG_LOAN)?,
+        parse_percent(matches, ARG_LOAN_RATE)?,
+        parse_percent(matches, ARG_PURCHASE_CHARGES)?,
+        parse_percent(matches, ARG_ANNUAL_CHARGES)?,
+        parse_percent(matches, ARG_HOME_APPRECIATION)?,
+        parse_value(matches, ARG_HOME_RENT)?,
+        parse_percent(matches, ARG_INVEST_RATE_RENT)?,
+        parse_value(matches, ARG_YEARS)?,
+    )?;
+    if let Some(area) = parse_optional_value(matches, ARG_AREA)? {
+        home_invest = home_invest.with_area(area);
+    }
+    for cost in matches.values_of(ARG_OWNER_COST).into_iter().flatten() {
+        home_invest = home_invest.with_owner_cost(cost.parse::<OwnerCost>()?);
+    }
+    for loan in matches.values_of(ARG_SAVINGS_LOAN).into_iter().flatten() {
+        home_invest = home_invest.with_loan(parse_savings_loan(loan)?)?;
+    }
+    let home_invest = home_invest.with_rent_plan(parse_rent_plan(matches)?);
+    let home_invest = if let Some(path) = matches.value_of(ARG_PRICE_INDEX_FILE) {
+        home_invest.with_price_path(load_index(Path::new(path))?)
+    } else if let Some(crash) = parse_optional_value::<Crash>(matches, ARG_PRICE_CRASH)? {
+        let price_path = crash.price_path(home_invest.annual_appreciation_rate)?;
+        home_invest.with_price_path(price_path)
+    } else {
+        home_invest
+    };
+    let home_invest = match parse_portfolio(matches)? {
+        Some(portfolio) => home_invest.with_portfolio(portfolio),
+        None => home_invest,
+    };
+    Ok(match parse_wrapper(matches)? {
+        Some(wrapper) => home_invest.with_wrapper(wrapper),
+        None => home_invest,
+    })
+}
+
+/// Parse how the rent evolves from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+fn parse_rent_plan<'a>(matches: &ArgMatches<'a>) -> Result<RentPlan, HomeCalcError> {
+    let mut plan = RentPlan::default();
+    if let Some(path) = matches.value_of(ARG_RENT_INDEX_FILE) {
+        plan = plan.with_indexation(load_index(Path::new(path))?);
+    } else if matches.is_present(ARG_RENT_INDEXATION) {
+        let indexation = parse_percent(matches, ARG_RENT_INDEXATION)?;
+        plan = plan.with_indexation(Inflation::constant(indexation)?);
+    }
+    for (from, rent) in parse_pairs(matches, ARG_RENT_CHANGE, ':')? {
+        plan = plan.with_change(from, rent);
+    }
+    Ok(plan)
+}
+
+/// Parse the loan of a home savings plan from `kind:opening:balance:deposit:months:years`
+///
+/// # Arguments
+/// * `value` - the value of the argument
+fn parse_savings_loan(value: &str) -> Result<Loan, HomeCalcError> {
+    let invalid = || HomeCalcError::InvalidValue {
+        name: ARG_SAVINGS_LOAN.to_string(),
+        value: value.to_string(),
+    };
+    let fields: Vec<&str> = value.split(':').map(str::trim).collect();
+    if fields.len() != 6 {
+        return Err(invalid());
+    }
+    let kind = fields[0].parse()?;
+    let opening: Date = fields[1].parse()?;
+    let numbers: Vec<u32> = fields[2..5]
+        .iter()
+        .map(|field| field.parse().map_err(|_| invalid()))
+        .collect::<Result<_, _>>()?;
+    let years = fields[5].parse().map_err(|_| invalid())?;
+    let account = RegulatedAccount::new(kind, numbers[0], opening)?;
+    HomeSavingsPlan::new(account, numbers[1])?.loan(numbers[2], years)
 }
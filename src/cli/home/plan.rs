@@ -0,0 +1,127 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::invest::{common_invest_args, parse_common_invest_args};
+use cli::{parse_percent, parse_value};
+use error::HomeCalcError;
+use purchase_plan::PurchasePlan;
+
+pub const SUB_HOME_PLAN: &str = "plan";
+const ARG_PRICE: &str = "price";
+const ARG_HOME_APPRECIATION: &str = "home-appreciation";
+const ARG_PURCHASE_CHARGES: &str = "purchase-charges";
+const ARG_DOWN_PAYMENT: &str = "down-payment";
+const ARG_MAX_YEARS: &str = "max-years";
+
+/// Returns the home plan sub command
+pub fn home_plan_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_HOME_PLAN)
+        .about("find when the savings cover the down payment of a home")
+        .args(common_invest_args().as_slice())
+        .arg(
+            Arg::with_name(ARG_PRICE)
+                .long(ARG_PRICE)
+                .short("v")
+                .takes_value(true)
+                .required(true)
+                .help("price of the home today, the purchase charges excluded"),
+        ).arg(
+            Arg::with_name(ARG_HOME_APPRECIATION)
+                .long(ARG_HOME_APPRECIATION)
+                .short("e")
+                .takes_value(true)
+                .required(false)
+                .default_value("0")
+                .help("the home appreciation by year in percent"),
+        ).arg(
+            Arg::with_name(ARG_PURCHASE_CHARGES)
+                .long(ARG_PURCHASE_CHARGES)
+                .short("P")
+                .takes_value(true)
+                .required(false)
+                .default_value("8")
+                .help("the purchase charges in percent of the price"),
+        ).arg(
+            Arg::with_name(ARG_DOWN_PAYMENT)
+                .long(ARG_DOWN_PAYMENT)
+                .short("o")
+                .takes_value(true)
+                .required(false)
+                .default_value("10")
+                .help("the down payment in percent of the price, on top of the purchase charges"),
+        ).arg(
+            Arg::with_name(ARG_MAX_YEARS)
+                .long(ARG_MAX_YEARS)
+                .short("M")
+                .takes_value(true)
+                .required(false)
+                .default_value("30")
+                .help("number of years after which the purchase is considered out of reach"),
+        )
+}
+
+/// Execute the work and print results for the plan sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the parameters
+pub fn execute_home_plan<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let savings = parse_common_invest_args(matches)?;
+    let periodicity = savings.periodicity;
+    let plan = PurchasePlan::new(
+        savings,
+        parse_value(matches, ARG_PRICE)?,
+        parse_percent(matches, ARG_HOME_APPRECIATION)?,
+        parse_percent(matches, ARG_PURCHASE_CHARGES)?,
+        parse_percent(matches, ARG_DOWN_PAYMENT)?,
+    )?;
+    let max_years: u32 = parse_value(matches, ARG_MAX_YEARS)?;
+
+    println!(
+        "*** For a home of {} with an appreciation of {}% by year, purchase charges of {}% and \
+         a down payment of {}%",
+        plan.price,
+        plan.appreciation * 100_f32,
+        plan.purchase_charges * 100_f32,
+        plan.down_payment * 100_f32
+    );
+    println!(
+        "*** Saving from {} with {} per period at a interest rate of {}% per year\n",
+        plan.savings.capital,
+        plan.savings.regular_addition,
+        plan.savings.yield_rate * 100_f32
+    );
+    let purchase = match plan.earliest_purchase(max_years * periodicity as u32) {
+        Ok(purchase) => purchase,
+        Err(HomeCalcError::NoSolution(_)) => {
+            println!("*** The purchase is out of reach within {} years ***", max_years);
+            return Ok(());
+        }
+        Err(error) => return Err(error),
+    };
+    let years_round = format!("{:.1}", purchase.period as f32 / periodicity as f32);
+    let mut table = table!(["title", "at (periods)", "at (~years)", "value"]);
+    table.add_row(row![
+        "home price",
+        purchase.period,
+        years_round,
+        format!("{:.02}", purchase.price)
+    ]);
+    table.add_row(row![
+        "down payment and purchase charges",
+        purchase.period,
+        years_round,
+        format!("{:.02}", purchase.required)
+    ]);
+    table.add_row(row![
+        "savings after tax",
+        purchase.period,
+        years_round,
+        format!("{:.02}", purchase.savings)
+    ]);
+    table.add_row(row![
+        "loan",
+        purchase.period,
+        years_round,
+        format!("{:.02}", purchase.loan)
+    ]);
+    table.printstd();
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use super::{common_home_args, parse_common_home_args};
+use clap::{App, ArgMatches, SubCommand};
+use cli::stress::{parse_stress_args, print_stress_results, stress_args};
+use error::HomeCalcError;
+use stress::{default_shocks, stress_home_purchase, Shock};
+
+pub const SUB_HOME_STRESS: &str = "stress";
+
+/// Returns the home stress sub command
+pub fn home_stress_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_HOME_STRESS)
+        .about("apply rate shocks to the loan of a planned home purchase")
+        .args(stress_args().as_slice())
+        .args(common_home_args().as_slice())
+}
+
+/// Execute the work and print results for the stress sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the parameters
+pub fn execute_home_stress<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let home_invest = parse_common_home_args(matches)?;
+    let (income, threshold) = parse_stress_args(matches)?;
+    let mut shocks = vec![Shock::Parallel(0_f32)];
+    shocks.extend(default_shocks());
+
+    println!(
+        "*** Stress test for a loan of {} on {} years with a rate of {}% before signing ***",
+        home_invest.loan,
+        home_invest.years,
+        home_invest.loan_rate * 100_f32
+    );
+    let results = stress_home_purchase(&home_invest, &shocks, income, threshold)?;
+    print_stress_results(&results, threshold);
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use clap::{Arg, ArgMatches};
+use cli::parse_percent;
+use error::HomeCalcError;
+use inflation::{load_inflation, Inflation};
+use std::path::Path;
+
+const ARG_REAL: &str = "real";
+const ARG_INFLATION: &str = "inflation";
+const ARG_INFLATION_FILE: &str = "inflation-file";
+
+/// Return the global arguments for the inflation assumption
+///
+/// They have no short name as they are shared by every sub command.
+pub fn inflation_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name(ARG_REAL)
+            .long(ARG_REAL)
+            .global(true)
+            .help("print amounts in today's money"),
+        Arg::with_name(ARG_INFLATION)
+            .long(ARG_INFLATION)
+            .takes_value(true)
+            .global(true)
+            .default_value("2")
+            .help("inflation by year in percent for the real amounts"),
+        Arg::with_name(ARG_INFLATION_FILE)
+            .long(ARG_INFLATION_FILE)
+            .takes_value(true)
+            .global(true)
+            .help("inflation of each year from a CSV file (year;rate) for the real amounts"),
+    ]
+}
+
+/// The conversion of the computed amounts before they are printed
+pub struct Money {
+    pub inflation: Option<Inflation>,
+    pub periodicity: u8,
+}
+
+impl Money {
+    /// Return an amount at the end of a period, in today's money in real mode
+    ///
+    /// # Arguments
+    /// * `amount` - the nominal amount
+    /// * `period` - the period
+    pub fn value(&self, amount: f64, period: u32) -> f64 {
+        match self.inflation {
+            Some(ref inflation) => inflation.real(amount, period, self.periodicity),
+            None => amount,
+        }
+    }
+
+    /// Return a total over several periods, each flow being in today's money in real mode
+    ///
+    /// # Arguments
+    /// * `nominal` - the nominal total
+    /// * `flow` - the nominal flow of a period, starting at 1
+    /// * `n_period` - number of periods
+    pub fn total<F: Fn(u32) -> Result<f64, HomeCalcError>>(
+        &self,
+        nominal: f64,
+        flow: F,
+        n_period: u32,
+    ) -> Result<f64, HomeCalcError> {
+        let inflation = match self.inflation {
+            Some(ref inflation) => inflation,
+            None => return Ok(nominal),
+        };
+        let mut total = 0_f64;
+        for period in 1..=n_period {
+            total += inflation.real(flow(period)?, period, self.periodicity);
+        }
+        Ok(total)
+    }
+
+    /// Print the inflation assumption in real mode
+    pub fn print_assumption(&self) {
+        if let Some(ref inflation) = self.inflation {
+            if inflation.rates.len() == 1 {
+                println!(
+                    "*** Amounts in today's money with an inflation of {}% by year ***\n",
+                    inflation.rates[0] * 100_f32
+                );
+            } else {
+                println!(
+                    "*** Amounts in today's money with the inflation of {} years ***\n",
+                    inflation.rates.len()
+                );
+            }
+        }
+    }
+}
+
+/// Parse the inflation assumption from the cli
+///
+/// # Arguments
+/// * `matches` - cli arguments matches
+/// * `periodicity` - number of periods by year of the computation
+pub fn parse_money<'a>(matches: &ArgMatches<'a>, periodicity: u8) -> Result<Money, HomeCalcError> {
+    let inflation = if !matches.is_present(ARG_REAL) {
+        None
+    } else if let Some(path) = matches.value_of(ARG_INFLATION_FILE) {
+        Some(load_inflation(Path::new(path))?)
+    } else {
+        Some(Inflation::constant(parse_percent(matches, ARG_INFLATION)?)?)
+    };
+    Ok(Money {
+        inflation,
+        periodicity,
+    })
+}
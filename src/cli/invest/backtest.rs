@@ -0,0 +1,69 @@
+use super::{common_invest_args, parse_common_invest_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::parse_value;
+use error::HomeCalcError;
+use history::{backtest, load_returns};
+use std::path::Path;
+
+pub const SUB_INVEST_BACKTEST: &str = "backtest";
+const ARG_PERIOD: &str = "n-periods";
+const ARG_HISTORY: &str = "history";
+
+/// Returns the invest backtest sub command
+pub fn invest_backtest_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_INVEST_BACKTEST)
+        .about("run an investment over every historical window of a number of periods")
+        .arg(
+            Arg::with_name(ARG_PERIOD)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).args(common_invest_args().as_slice())
+        .arg(
+            Arg::with_name(ARG_HISTORY)
+                .long(ARG_HISTORY)
+                .short("H")
+                .takes_value(true)
+                .required(true)
+                .help("CSV file of historical returns by period (date;return in percent)"),
+        )
+}
+
+/// Execute the work and print results for the backtest sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_invest_backtest<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let invest = parse_common_invest_args(matches)?;
+    let n_period = parse_value(matches, ARG_PERIOD)?;
+    let series = load_returns(Path::new(matches.value_of(ARG_HISTORY).unwrap_or("")))?;
+    let result = backtest(&invest, &series, n_period)?;
+    let total_invest = invest.capital as f64 + invest.additions_total(n_period);
+
+    println!("*** For an investment of {} and regular additions of {} per period over {} periods (~{:.1} years)\n",
+            invest.capital, invest.regular_addition, n_period,
+            n_period as f32 / invest.periodicity as f32);
+    if let Some(ref portfolio) = invest.portfolio {
+        println!("*** The capital is spread over a portfolio: {} ***\n", portfolio);
+    }
+    println!(
+        "*** {} historical windows, {:.2} invested in each ***\n",
+        result.outcomes.len(),
+        total_invest
+    );
+    let mut backtest_table = table!(["outcome", "start", "capital", "gain"]);
+    for &(title, (start, capital)) in &[
+        ("worst", result.worst()),
+        ("median", result.median()),
+        ("best", result.best()),
+    ] {
+        backtest_table.add_row(row![
+            title,
+            start,
+            format!("{:.2}", capital),
+            format!("{:.2}", capital - total_invest),
+        ]);
+    }
+    backtest_table.printstd();
+    Ok(())
+}
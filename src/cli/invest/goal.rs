@@ -0,0 +1,94 @@
+use super::{common_invest_args, parse_common_invest_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{or_none, parse_value};
+use error::{check_duration, HomeCalcError};
+
+pub const SUB_INVEST_GOAL: &str = "goal";
+const ARG_TARGET: &str = "target";
+const ARG_AT: &str = "at";
+const ARG_MAX_YEARS: &str = "max-years";
+
+/// Returns the invest goal sub command
+pub fn invest_goal_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_INVEST_GOAL)
+        .about("find how to reach a target capital at a period")
+        .arg(
+            Arg::with_name(ARG_TARGET)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_AT)
+                .takes_value(true)
+                .required(true)
+                .index(2),
+        ).args(common_invest_args().as_slice())
+        .arg(
+            Arg::with_name(ARG_MAX_YEARS)
+                .long(ARG_MAX_YEARS)
+                .short("M")
+                .takes_value(true)
+                .required(false)
+                .default_value("100")
+                .help("number of years after which the target is considered out of reach"),
+        )
+}
+
+/// Execute the work and print results for the goal sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_invest_goal<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let invest = parse_common_invest_args(matches)?;
+    let target: f64 = parse_value(matches, ARG_TARGET)?;
+    let at = check_duration(ARG_AT, parse_value(matches, ARG_AT)?)?;
+    let max_years: u32 = parse_value(matches, ARG_MAX_YEARS)?;
+    let years_round = format!("{:.1}", at as f32 / invest.periodicity as f32);
+
+    println!("*** To reach {} at period {} (~{} years) with an investment of {}, regular additions of {} per period at a interest rate of {}% per year\n",
+            target, at, years_round, invest.capital, invest.regular_addition,
+            invest.yield_rate * 100_f32);
+    if let Some(ref portfolio) = invest.portfolio {
+        println!("*** The capital is spread over a portfolio: {} ***\n", portfolio);
+    }
+    let mut goal_table = table!(["title", "at (periods)", "at (~years)", "value"]);
+    goal_table.add_row(row![
+        "capital with the current plan",
+        at,
+        years_round,
+        format!("{:.02}", invest.capital_at(at))
+    ]);
+    goal_table.add_row(row![
+        "required regular addition",
+        at,
+        years_round,
+        or_none(invest.required_addition(target, at))?
+    ]);
+    if invest.portfolio.is_none() {
+        goal_table.add_row(row![
+            "required yield rate (%)",
+            at,
+            years_round,
+            or_none(
+                invest
+                    .required_yield(target, at)
+                    .map(|rate| format!("{:.02}", rate * 100_f32))
+            )?
+        ]);
+    }
+    let periods = invest.periods_to_reach(target, max_years * invest.periodicity as u32);
+    goal_table.add_row(row![
+        "periods needed with the current plan",
+        "NONE",
+        "NONE",
+        or_none(periods.map(|period| {
+            format!(
+                "{} (~{:.1} years)",
+                period,
+                period as f32 / invest.periodicity as f32
+            )
+        }))?
+    ]);
+    goal_table.printstd();
+    Ok(())
+}
@@ -1,5 +1,9 @@
 use super::{common_invest_args, parse_common_invest_args};
+use cash_flow::{irr, yearly_rate};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::inflation::parse_money;
+use cli::{or_none, parse_value};
+use error::HomeCalcError;
 
 pub const SUB_INVEST_INFO_AT: &str = "info-at";
 const ARG_PERIOD: &str = "n-periods";
@@ -20,24 +24,38 @@ pub fn invest_info_subcommand<'a, 'b>() -> App<'a, 'b> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_invest_info_at<'a>(matches: &ArgMatches<'a>) {
-    let invest = parse_common_invest_args(matches);
-    let at = matches
-        .value_of(ARG_PERIOD)
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
+pub fn execute_invest_info_at<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let invest = parse_common_invest_args(matches)?;
+    let at = parse_value(matches, ARG_PERIOD)?;
+    let money = parse_money(matches, invest.periodicity)?;
     let years_round = format!("{:.1}", at as f32 / invest.periodicity as f32);
-    let total_invest = invest.capital + invest.additions_total(at);
-    let capital = invest.capital_at(at);
+    let additions = money.total(
+        invest.additions_total(at),
+        |period| Ok(invest.contribution_at(period)),
+        at,
+    )?;
+    let total_invest = invest.capital as f64 + additions;
+    let capital = money.value(invest.capital_at(at), at);
+    let withdrawn = money.total(
+        invest.withdrawals_total(at),
+        |period| Ok(invest.withdrawal_at(period)),
+        at,
+    )?;
     println!("*** For an investment of {} and regular additions of {} per period at a interest rate of {} per year\n",
             invest.capital, invest.regular_addition, invest.yield_rate);
+    if let Some(ref curve) = invest.curve {
+        println!("*** The yield rate changes over time: {} ***\n", curve);
+    }
+    if let Some(ref portfolio) = invest.portfolio {
+        println!("*** The capital is spread over a portfolio: {} ***\n", portfolio);
+    }
+    money.print_assumption();
     let mut invest_table = table!(["title", "at (periods)", "at (~years)", "value"]);
     invest_table.add_row(row![
         "total additions",
         "NONE",
         "NONE",
-        format!("{:.02}", invest.additions_total(at))
+        format!("{:.02}", additions)
     ]);
     invest_table.add_row(row![
         "total investment",
@@ -49,13 +67,83 @@ pub fn execute_invest_info_at<'a>(matches: &ArgMatches<'a>) {
         "capital",
         at,
         years_round,
-        format!("{:.02}", invest.capital_at(at))
+        format!("{:.02}", capital)
     ]);
     invest_table.add_row(row![
         "interest earned",
         at,
         years_round,
-        format!("{:.02}", capital - total_invest as f64)
+        format!("{:.02}", capital + withdrawn - total_invest)
+    ]);
+    if !invest.fees.is_free() {
+        let fees = money.total(
+            invest.fees_total(at),
+            |period| Ok(invest.fees_total(period) - invest.fees_total(period - 1)),
+            at,
+        )?;
+        invest_table.add_row(row!["total fees", at, years_round, format!("{:.02}", fees)]);
+        invest_table.add_row(row![
+            "capital without fees",
+            at,
+            years_round,
+            format!("{:.02}", money.value(invest.gross_capital_at(at), at))
+        ]);
+    }
+    if let Some(withdrawals) = invest.withdrawals {
+        invest_table.add_row(row![
+            "total withdrawals",
+            at,
+            years_round,
+            format!("{:.02}", withdrawn)
+        ]);
+        invest_table.add_row(row![
+            "capital depleted at (periods)",
+            "NONE",
+            "NONE",
+            match invest.depletion_period(at) {
+                Some(period) => period.to_string(),
+                None => "NONE".to_string(),
+            }
+        ]);
+        if at >= withdrawals.start {
+            invest_table.add_row(row![
+                "sustainable withdrawal by period",
+                at,
+                years_round,
+                match invest.sustainable_withdrawal(withdrawals.start, at - withdrawals.start + 1) {
+                    Ok(amount) => format!("{:.02}", money.value(amount, withdrawals.start)),
+                    Err(_) => "NONE".to_string(),
+                }
+            ]);
+        }
+    }
+    if let Some(wrapper) = invest.wrapper {
+        let taxes = money.total(
+            invest.taxes_total(at),
+            |period| Ok(invest.taxes_total(period) - invest.taxes_total(period - 1)),
+            at,
+        )?;
+        invest_table.add_row(row![
+            format!("taxes on withdrawals ({})", wrapper),
+            at,
+            years_round,
+            format!("{:.02}", taxes)
+        ]);
+        invest_table.add_row(row![
+            "capital after tax",
+            at,
+            years_round,
+            format!("{:.02}", money.value(invest.after_tax_capital_at(at), at))
+        ]);
+    }
+    let rate = irr(&invest.cash_flows(at))
+        .map(|rate| yearly_rate(rate, invest.periodicity) * 100_f64);
+    invest_table.add_row(row![
+        "money-weighted return (% by year)",
+        at,
+        years_round,
+        or_none(rate.map(|rate| format!("{:.02}", rate)))?
     ]);
     invest_table.printstd();
+    Ok(())
 }
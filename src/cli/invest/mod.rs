@@ -1,10 +1,33 @@
+mod backtest;
+mod goal;
 mod info;
+mod monte_carlo;
+mod regulated;
 mod table;
+mod xirr;
 
+use self::backtest::{execute_invest_backtest, invest_backtest_subcommand, SUB_INVEST_BACKTEST};
+use self::goal::{execute_invest_goal, invest_goal_subcommand, SUB_INVEST_GOAL};
 use self::info::{execute_invest_info_at, invest_info_subcommand, SUB_INVEST_INFO_AT};
+use self::monte_carlo::{
+    execute_invest_monte_carlo, invest_monte_carlo_subcommand, SUB_INVEST_MONTE_CARLO,
+};
+use self::regulated::{
+    execute_invest_regulated, invest_regulated_subcommand, SUB_INVEST_REGULATED,
+};
 use self::table::{execute_invest_table, invest_table_subcommand, SUB_INVEST_TABLE};
+use self::xirr::{execute_invest_xirr, invest_xirr_subcommand, SUB_INVEST_XIRR};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{parse_optional_value, parse_pairs, parse_percent, parse_value};
+use compounding::Compounding;
+use contribution::{ContributionPlan, Timing};
+use error::HomeCalcError;
+use fees::Fees;
 use investment::Investment;
+use portfolio::{Portfolio, Rebalancing};
+use tax::Wrapper;
+use withdrawal::{Withdrawal, WithdrawalPlan};
+use yield_curve::YieldCurve;
 
 /// The invest sub command string
 pub const SUB_INVEST: &str = "invest";
@@ -12,10 +35,35 @@ const ARG_PERIODICITY: &str = "period";
 const ARG_YIELD_RATE: &str = "yield";
 const ARG_CAPITAL: &str = "capital";
 const ARG_REGULAR_ADDITION: &str = "addition";
+const ARG_STEP_UP: &str = "step-up";
+const ARG_CHANGE: &str = "change";
+const ARG_PAUSE: &str = "pause";
+const ARG_LUMP_SUM: &str = "lump-sum";
+const ARG_WITHDRAWAL_START: &str = "withdrawal-start";
+const ARG_WITHDRAWAL: &str = "withdrawal";
+const ARG_WITHDRAWAL_RATE: &str = "withdrawal-rate";
+const ARG_WITHDRAWAL_INDEXATION: &str = "withdrawal-indexation";
+const ARG_WRAPPER: &str = "wrapper";
+const ARG_ENTRY_FEES: &str = "entry-fees";
+const ARG_MANAGEMENT_FEES: &str = "management-fees";
+const ARG_TIMING: &str = "timing";
+const ARG_COMPOUNDING: &str = "compounding";
+const ARG_YIELD_PHASE: &str = "yield-phase";
+const ARG_GLIDE_PATH: &str = "glide-path";
+const ARG_ASSET: &str = "asset";
+const ARG_REBALANCING: &str = "rebalancing";
 
 /// Returns the loan sub command
 pub fn invest_sub_commands<'a, 'b>() -> Vec<App<'a, 'b>> {
-    let invest_sub_commands = vec![invest_info_subcommand(), invest_table_subcommand()];
+    let invest_sub_commands = vec![
+        invest_info_subcommand(),
+        invest_table_subcommand(),
+        invest_monte_carlo_subcommand(),
+        invest_backtest_subcommand(),
+        invest_goal_subcommand(),
+        invest_xirr_subcommand(),
+        invest_regulated_subcommand(),
+    ];
     let sub_commands = vec![SubCommand::with_name(SUB_INVEST).subcommands(invest_sub_commands)];
     sub_commands
 }
@@ -24,11 +72,23 @@ pub fn invest_sub_commands<'a, 'b>() -> Vec<App<'a, 'b>> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_invest_sub_command<'a>(matches: &ArgMatches<'a>) {
+pub fn execute_invest_sub_command<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
     match matches.subcommand() {
         (SUB_INVEST_INFO_AT, Some(info_at_matches)) => execute_invest_info_at(info_at_matches),
         (SUB_INVEST_TABLE, Some(table_matches)) => execute_invest_table(table_matches),
-        _ => println!("*** No command found"),
+        (SUB_INVEST_MONTE_CARLO, Some(monte_carlo_matches)) => {
+            execute_invest_monte_carlo(monte_carlo_matches)
+        }
+        (SUB_INVEST_BACKTEST, Some(backtest_matches)) => execute_invest_backtest(backtest_matches),
+        (SUB_INVEST_GOAL, Some(goal_matches)) => execute_invest_goal(goal_matches),
+        (SUB_INVEST_XIRR, Some(xirr_matches)) => execute_invest_xirr(xirr_matches),
+        (SUB_INVEST_REGULATED, Some(regulated_matches)) => {
+            execute_invest_regulated(regulated_matches)
+        }
+        _ => {
+            println!("*** No command found");
+            Ok(())
+        }
     }
 }
 
@@ -60,35 +120,299 @@ pub fn common_invest_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
             .required(false)
             .default_value("0")
             .help("regular addition"),
+        Arg::with_name(ARG_STEP_UP)
+            .long(ARG_STEP_UP)
+            .short("s")
+            .takes_value(true)
+            .required(false)
+            .default_value("0")
+            .help("yearly increase of the regular addition in percent"),
+        Arg::with_name(ARG_CHANGE)
+            .long(ARG_CHANGE)
+            .short("n")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help("new regular addition from a period (period:amount)"),
+        Arg::with_name(ARG_PAUSE)
+            .long(ARG_PAUSE)
+            .short("z")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help("periods without regular addition (from-to)"),
+        Arg::with_name(ARG_LUMP_SUM)
+            .long(ARG_LUMP_SUM)
+            .short("l")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .help("one-off addition at the end of a period (period:amount)"),
+        Arg::with_name(ARG_WITHDRAWAL_START)
+            .long(ARG_WITHDRAWAL_START)
+            .short("w")
+            .takes_value(true)
+            .required(false)
+            .help("first period of withdrawal"),
+        Arg::with_name(ARG_WITHDRAWAL)
+            .long(ARG_WITHDRAWAL)
+            .short("d")
+            .takes_value(true)
+            .required(false)
+            .requires(ARG_WITHDRAWAL_START)
+            .conflicts_with(ARG_WITHDRAWAL_RATE)
+            .help("amount withdrawn by period"),
+        Arg::with_name(ARG_WITHDRAWAL_RATE)
+            .long(ARG_WITHDRAWAL_RATE)
+            .short("k")
+            .takes_value(true)
+            .required(false)
+            .requires(ARG_WITHDRAWAL_START)
+            .help("percentage of the balance withdrawn by year"),
+        Arg::with_name(ARG_WITHDRAWAL_INDEXATION)
+            .long(ARG_WITHDRAWAL_INDEXATION)
+            .short("x")
+            .takes_value(true)
+            .required(false)
+            .requires(ARG_WITHDRAWAL)
+            .help("yearly increase of the amount withdrawn in percent (e.g. inflation)"),
+        wrapper_arg(),
+        Arg::with_name(ARG_ENTRY_FEES)
+            .long(ARG_ENTRY_FEES)
+            .short("f")
+            .takes_value(true)
+            .required(false)
+            .default_value("0")
+            .help("fees on each addition in percent"),
+        Arg::with_name(ARG_MANAGEMENT_FEES)
+            .long(ARG_MANAGEMENT_FEES)
+            .short("g")
+            .takes_value(true)
+            .required(false)
+            .default_value("0")
+            .help("management fees on the capital in percent by year"),
+        Arg::with_name(ARG_TIMING)
+            .long(ARG_TIMING)
+            .short("T")
+            .takes_value(true)
+            .required(false)
+            .default_value("end")
+            .possible_values(&["end", "start"])
+            .help("additions at the start or at the end of each period"),
+        Arg::with_name(ARG_COMPOUNDING)
+            .long(ARG_COMPOUNDING)
+            .short("C")
+            .takes_value(true)
+            .required(false)
+            .help("compoundings by year or 'continuous', at each period by default"),
+        Arg::with_name(ARG_YIELD_PHASE)
+            .long(ARG_YIELD_PHASE)
+            .short("Y")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1)
+            .required(false)
+            .conflicts_with(ARG_GLIDE_PATH)
+            .help("new yield by year in percent from a period (period:yield)"),
+        Arg::with_name(ARG_GLIDE_PATH)
+            .long(ARG_GLIDE_PATH)
+            .short("G")
+            .takes_value(true)
+            .required(false)
+            .help(
+                "yield moving toward a final yield between two ages \
+                 (age:start-age:end-age:end-yield in percent)",
+            ),
+        asset_arg(),
+        rebalancing_arg(),
     ]
 }
 
+/// Return the argument for the account holding an investment
+pub fn wrapper_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_WRAPPER)
+        .long(ARG_WRAPPER)
+        .short("W")
+        .takes_value(true)
+        .required(false)
+        .possible_values(&[
+            "taxable",
+            "pea",
+            "assurance-vie",
+            "assurance-vie-couple",
+            "livret-a",
+        ]).help("account holding the investment, gains are taxed on withdrawal")
+}
+
+/// Return the argument for the asset classes of a portfolio
+pub fn asset_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_ASSET)
+        .long(ARG_ASSET)
+        .short("A")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .required(false)
+        .help("asset class of a portfolio replacing the yield rate (name:yield:volatility:weight)")
+}
+
+/// Return the argument for the rebalancing rule of a portfolio
+pub fn rebalancing_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name(ARG_REBALANCING)
+        .long(ARG_REBALANCING)
+        .short("R")
+        .takes_value(true)
+        .required(false)
+        .requires(ARG_ASSET)
+        .help("rebalancing of the portfolio (never, every:<periods> or threshold:<percent>)")
+}
+
+/// Parse the portfolio of an investment from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+pub fn parse_portfolio<'a>(matches: &ArgMatches<'a>) -> Result<Option<Portfolio>, HomeCalcError> {
+    let assets = match matches.values_of(ARG_ASSET) {
+        Some(assets) => assets
+            .map(|asset| asset.parse())
+            .collect::<Result<Vec<_>, _>>()?,
+        None => return Ok(None),
+    };
+    let rebalancing = parse_optional_value(matches, ARG_REBALANCING)?;
+    Portfolio::new(assets, rebalancing.unwrap_or(Rebalancing::Never)).map(Some)
+}
+
+/// Parse the account holding an investment from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+pub fn parse_wrapper<'a>(matches: &ArgMatches<'a>) -> Result<Option<Wrapper>, HomeCalcError> {
+    parse_optional_value(matches, ARG_WRAPPER)
+}
+
 /// Parse the common investment arguments from the cli
 ///
 /// # Arguments
 /// *  `matches` - cli arguments matches
-pub fn parse_common_invest_args<'a>(matches: &ArgMatches<'a>) -> Investment {
-    Investment::new(
-        matches
-            .value_of(ARG_CAPITAL)
-            .unwrap()
-            .parse::<u32>()
-            .unwrap(),
-        matches
-            .value_of(ARG_PERIODICITY)
-            .unwrap()
-            .parse::<u8>()
-            .unwrap(),
-        matches
-            .value_of(ARG_YIELD_RATE)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_REGULAR_ADDITION)
-            .unwrap()
-            .parse::<u32>()
-            .unwrap(),
-    )
+pub fn parse_common_invest_args<'a>(
+    matches: &ArgMatches<'a>,
+) -> Result<Investment, HomeCalcError> {
+    let plan = parse_contribution_plan(matches)?;
+    let fees = Fees::new(
+        parse_percent(matches, ARG_ENTRY_FEES)?,
+        parse_percent(matches, ARG_MANAGEMENT_FEES)?,
+    )?;
+    let invest = Investment::new(
+        parse_value(matches, ARG_CAPITAL)?,
+        parse_value(matches, ARG_PERIODICITY)?,
+        parse_percent(matches, ARG_YIELD_RATE)?,
+        parse_value(matches, ARG_REGULAR_ADDITION)?,
+    )?.with_plan(plan)
+        .with_fees(fees)
+        .with_timing(parse_value::<Timing>(matches, ARG_TIMING)?);
+    let invest = match parse_optional_value::<Compounding>(matches, ARG_COMPOUNDING)? {
+        Some(compounding) => invest.with_compounding(compounding),
+        None => invest,
+    };
+    let invest = match parse_yield_curve(matches)? {
+        Some(curve) => invest.with_curve(curve),
+        None => invest,
+    };
+    let invest = match parse_withdrawal_plan(matches)? {
+        Some(withdrawals) => invest.with_withdrawals(withdrawals),
+        None => invest,
+    };
+    let invest = match parse_portfolio(matches)? {
+        Some(portfolio) => invest.with_portfolio(portfolio),
+        None => invest,
+    };
+    Ok(match parse_wrapper(matches)? {
+        Some(wrapper) => invest.with_wrapper(wrapper),
+        None => invest,
+    })
+}
+
+/// Parse the yield curve from the cli, yield phases or a glide path
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+fn parse_yield_curve<'a>(matches: &ArgMatches<'a>) -> Result<Option<YieldCurve>, HomeCalcError> {
+    if let Some(glide_path) = parse_optional_value(matches, ARG_GLIDE_PATH)? {
+        return Ok(Some(glide_path));
+    }
+    let phases = match matches.values_of(ARG_YIELD_PHASE) {
+        Some(phases) => phases,
+        None => return Ok(None),
+    };
+    let phases = phases
+        .map(|phase| {
+            let invalid = || HomeCalcError::InvalidValue {
+                name: ARG_YIELD_PHASE.to_string(),
+                value: phase.to_string(),
+            };
+            let mut fields = phase.splitn(2, ':');
+            match (fields.next(), fields.next()) {
+                (Some(from), Some(yield_rate)) => Ok((
+                    from.trim().parse().map_err(|_| invalid())?,
+                    yield_rate.trim().parse::<f32>().map_err(|_| invalid())? / 100_f32,
+                )),
+                _ => Err(invalid()),
+            }
+        }).collect::<Result<Vec<_>, _>>()?;
+    YieldCurve::phases(phases).map(Some)
+}
+
+/// Parse the withdrawal plan from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+fn parse_withdrawal_plan<'a>(
+    matches: &ArgMatches<'a>,
+) -> Result<Option<WithdrawalPlan>, HomeCalcError> {
+    let start = match parse_optional_value(matches, ARG_WITHDRAWAL_START)? {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let withdrawal = match (
+        parse_optional_value(matches, ARG_WITHDRAWAL)?,
+        parse_optional_value::<f32>(matches, ARG_WITHDRAWAL_RATE)?,
+        parse_optional_value::<f32>(matches, ARG_WITHDRAWAL_INDEXATION)?,
+    ) {
+        (Some(amount), _, Some(inflation)) => Withdrawal::Indexed {
+            amount,
+            inflation: inflation / 100_f32,
+        },
+        (Some(amount), _, None) => Withdrawal::Fixed(amount),
+        (None, Some(rate), _) => Withdrawal::Percentage(rate / 100_f32),
+        (None, None, _) => {
+            return Err(HomeCalcError::InvalidValue {
+                name: ARG_WITHDRAWAL.to_string(),
+                value: String::new(),
+            })
+        }
+    };
+    WithdrawalPlan::new(start, withdrawal).map(Some)
+}
+
+/// Parse the contribution plan from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+fn parse_contribution_plan<'a>(
+    matches: &ArgMatches<'a>,
+) -> Result<ContributionPlan, HomeCalcError> {
+    let mut plan = ContributionPlan::new(parse_percent(matches, ARG_STEP_UP)?)?;
+    for (from, amount) in parse_pairs(matches, ARG_CHANGE, ':')? {
+        plan = plan.with_change(from, amount);
+    }
+    for (from, to) in parse_pairs(matches, ARG_PAUSE, '-')? {
+        plan = plan.with_pause(from, to);
+    }
+    for (period, amount) in parse_pairs(matches, ARG_LUMP_SUM, ':')? {
+        plan = plan.with_lump_sum(period, amount);
+    }
+    Ok(plan)
 }
@@ -0,0 +1,170 @@
+use super::{common_invest_args, parse_common_invest_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{parse_optional_value, parse_percent, parse_value};
+use error::{check_duration, HomeCalcError};
+use history::load_returns;
+use monte_carlo::{simulate, Distribution};
+use std::path::Path;
+
+pub const SUB_INVEST_MONTE_CARLO: &str = "monte-carlo";
+const ARG_EVERY_PERIOD: &str = "every-period";
+const ARG_TO: &str = "to";
+const ARG_DISTRIBUTION: &str = "distribution";
+const ARG_VOLATILITY: &str = "volatility";
+const ARG_DEGREES: &str = "degrees";
+const ARG_HISTORY: &str = "history";
+const ARG_SIMULATIONS: &str = "simulations";
+const ARG_SEED: &str = "seed";
+const ARG_TARGET: &str = "target";
+
+/// Returns the invest monte-carlo sub command
+pub fn invest_monte_carlo_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_INVEST_MONTE_CARLO)
+        .about("print the percentiles of the capital of an investment with random returns")
+        .arg(
+            Arg::with_name(ARG_EVERY_PERIOD)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_TO)
+                .takes_value(true)
+                .required(true)
+                .index(2),
+        ).args(common_invest_args().as_slice())
+        .arg(
+            Arg::with_name(ARG_DISTRIBUTION)
+                .long(ARG_DISTRIBUTION)
+                .short("D")
+                .takes_value(true)
+                .required(false)
+                .default_value("normal")
+                .possible_values(&["normal", "log-normal", "student-t", "bootstrap"])
+                .help("distribution of the returns, the yield rate being their mean"),
+        ).arg(
+            Arg::with_name(ARG_VOLATILITY)
+                .long(ARG_VOLATILITY)
+                .short("v")
+                .takes_value(true)
+                .required(false)
+                .default_value("15")
+                .help("volatility of the returns in percent by year"),
+        ).arg(
+            Arg::with_name(ARG_DEGREES)
+                .long(ARG_DEGREES)
+                .short("o")
+                .takes_value(true)
+                .required(false)
+                .default_value("5")
+                .help("degrees of freedom of the student-t distribution"),
+        ).arg(
+            Arg::with_name(ARG_HISTORY)
+                .long(ARG_HISTORY)
+                .short("H")
+                .takes_value(true)
+                .required_if(ARG_DISTRIBUTION, "bootstrap")
+                .help("CSV file of historical returns by period (date;return in percent)"),
+        ).arg(
+            Arg::with_name(ARG_SIMULATIONS)
+                .long(ARG_SIMULATIONS)
+                .short("m")
+                .takes_value(true)
+                .required(false)
+                .default_value("1000")
+                .help("number of simulations"),
+        ).arg(
+            Arg::with_name(ARG_SEED)
+                .long(ARG_SEED)
+                .short("e")
+                .takes_value(true)
+                .required(false)
+                .default_value("42")
+                .help("seed of the random generator, the same seed gives the same results"),
+        ).arg(
+            Arg::with_name(ARG_TARGET)
+                .long(ARG_TARGET)
+                .short("t")
+                .takes_value(true)
+                .required(false)
+                .help("capital to reach at the last period"),
+        )
+}
+
+/// Execute the work and print results for the monte-carlo sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_invest_monte_carlo<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let invest = parse_common_invest_args(matches)?;
+    let every = check_duration(ARG_EVERY_PERIOD, parse_value(matches, ARG_EVERY_PERIOD)?)?;
+    let to = parse_value(matches, ARG_TO)?;
+    let distribution = parse_distribution(matches, invest.yield_rate)?;
+    let simulations = parse_value(matches, ARG_SIMULATIONS)?;
+    let simulation = simulate(
+        &invest,
+        &distribution,
+        to,
+        simulations,
+        parse_value(matches, ARG_SEED)?,
+    )?;
+
+    println!("*** For an investment of {} and regular additions of {} per period with random returns of {}% per year on average\n",
+            invest.capital, invest.regular_addition, invest.yield_rate * 100_f32);
+    if let Some(ref portfolio) = invest.portfolio {
+        println!("*** The capital is spread over a portfolio: {} ***\n", portfolio);
+    }
+    println!(
+        "*** {} simulations with {} returns ***\n",
+        simulations, distribution
+    );
+
+    let mut bands_table = table!(["At (periods)", "At (~years)", "P5", "P25", "P50", "P75", "P95"]);
+    let periods = (0..to).step_by(every as usize).chain(Some(to));
+    for at in periods {
+        let bands = simulation.bands(at)?;
+        bands_table.add_row(row![
+            bands.period,
+            format!("{:.1}", bands.period as f32 / invest.periodicity as f32),
+            format!("{:.2}", bands.p5),
+            format!("{:.2}", bands.p25),
+            format!("{:.2}", bands.p50),
+            format!("{:.2}", bands.p75),
+            format!("{:.2}", bands.p95),
+        ]);
+    }
+    bands_table.printstd();
+    if let Some(target) = parse_optional_value(matches, ARG_TARGET)? {
+        println!(
+            "\n*** The probability to reach {} at period {} is {:.1}% ***",
+            target,
+            to,
+            simulation.probability_of(target, to)? * 100_f64
+        );
+    }
+    Ok(())
+}
+
+/// Parse the distribution of the returns from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+/// *  `mean` - the mean yearly return
+fn parse_distribution<'a>(
+    matches: &ArgMatches<'a>,
+    mean: f32,
+) -> Result<Distribution, HomeCalcError> {
+    let volatility = parse_percent(matches, ARG_VOLATILITY)?;
+    Ok(match matches.value_of(ARG_DISTRIBUTION) {
+        Some("log-normal") => Distribution::LogNormal { mean, volatility },
+        Some("student-t") => Distribution::StudentT {
+            mean,
+            volatility,
+            degrees: parse_value(matches, ARG_DEGREES)?,
+        },
+        Some("bootstrap") => {
+            let path = matches.value_of(ARG_HISTORY).unwrap_or("");
+            Distribution::Bootstrap(load_returns(Path::new(path))?.returns())
+        }
+        _ => Distribution::Normal { mean, volatility },
+    })
+}
@@ -0,0 +1,157 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{parse_optional_value, parse_percent, parse_value};
+use date::Date;
+use error::{check_duration, HomeCalcError};
+use home_savings::HomeSavingsPlan;
+use investment::Investment;
+use regulated::{load_rates, AccountKind, RegulatedAccount};
+use std::path::Path;
+
+pub const SUB_INVEST_REGULATED: &str = "regulated";
+const ARG_MONTHS: &str = "n-months";
+const ARG_ACCOUNT: &str = "account";
+const ARG_OPENING: &str = "opening";
+const ARG_BALANCE: &str = "balance";
+const ARG_DEPOSIT: &str = "deposit";
+const ARG_RATES_FILE: &str = "rates-file";
+const ARG_OVERFLOW_YIELD: &str = "overflow-yield";
+const ARG_LOAN_YEARS: &str = "loan-years";
+
+/// Returns the invest regulated sub command
+pub fn invest_regulated_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_INVEST_REGULATED)
+        .about("compute the savings on a regulated account fed every month")
+        .arg(
+            Arg::with_name(ARG_MONTHS)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_ACCOUNT)
+                .long(ARG_ACCOUNT)
+                .short("a")
+                .takes_value(true)
+                .required(false)
+                .default_value("livret-a")
+                .possible_values(&["livret-a", "ldds", "pel", "cel"])
+                .help("kind of account"),
+        ).arg(
+            Arg::with_name(ARG_OPENING)
+                .long(ARG_OPENING)
+                .short("o")
+                .takes_value(true)
+                .required(true)
+                .help("date of the opening of the plan, e.g. 2024-01-05"),
+        ).arg(
+            Arg::with_name(ARG_BALANCE)
+                .long(ARG_BALANCE)
+                .short("b")
+                .takes_value(true)
+                .required(false)
+                .default_value("0")
+                .help("balance at the opening"),
+        ).arg(
+            Arg::with_name(ARG_DEPOSIT)
+                .long(ARG_DEPOSIT)
+                .short("d")
+                .takes_value(true)
+                .required(false)
+                .default_value("0")
+                .help("amount deposited every month"),
+        ).arg(
+            Arg::with_name(ARG_RATES_FILE)
+                .long(ARG_RATES_FILE)
+                .short("H")
+                .takes_value(true)
+                .required(false)
+                .help("CSV file of the rates (date;rate in percent) instead of the official ones"),
+        ).arg(
+            Arg::with_name(ARG_OVERFLOW_YIELD)
+                .long(ARG_OVERFLOW_YIELD)
+                .short("y")
+                .takes_value(true)
+                .required(false)
+                .default_value("0")
+                .help("yield by year in percent of the investment receiving the overflows"),
+        ).arg(
+            Arg::with_name(ARG_LOAN_YEARS)
+                .long(ARG_LOAN_YEARS)
+                .short("l")
+                .takes_value(true)
+                .required(false)
+                .help("years of the loan granted at the end of a PEL or a CEL"),
+        )
+}
+
+/// Execute the work and print results for the regulated sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_invest_regulated<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let n_months = check_duration(ARG_MONTHS, parse_value(matches, ARG_MONTHS)?)?;
+    let kind: AccountKind = parse_value(matches, ARG_ACCOUNT)?;
+    let opening: Date = parse_value(matches, ARG_OPENING)?;
+    let deposit = parse_value(matches, ARG_DEPOSIT)?;
+    let overflow = Investment::new(0, 12, parse_percent(matches, ARG_OVERFLOW_YIELD)?, 0)?;
+    let mut account = RegulatedAccount::new(kind, parse_value(matches, ARG_BALANCE)?, opening)?
+        .with_overflow(overflow);
+    if let Some(path) = matches.value_of(ARG_RATES_FILE) {
+        account = account.with_rates(load_rates(Path::new(path))?);
+    }
+    let savings = account.savings(deposit, n_months);
+    let overflows: f64 = savings.overflows.iter().sum();
+
+    println!(
+        "*** For a {} opened on {}-{:02}-{:02} with {} and deposits of {} per month ***\n",
+        kind, opening.year, opening.month, opening.day, account.balance, deposit
+    );
+    println!("*** The deposits are capped at {} ***\n", kind.ceiling());
+    let mut savings_table = table!(["title", "at (months)", "value"]);
+    savings_table.add_row(row![
+        "balance",
+        n_months,
+        format!("{:.02}", savings.balance)
+    ]);
+    savings_table.add_row(row![
+        "interest earned",
+        n_months,
+        format!("{:.02}", savings.interest)
+    ]);
+    savings_table.add_row(row![
+        "deposits over the ceiling",
+        n_months,
+        format!("{:.02}", overflows)
+    ]);
+    savings_table.add_row(row![
+        "capital of the overflows",
+        n_months,
+        format!("{:.02}", savings.overflow_capital)
+    ]);
+    savings_table.add_row(row![
+        "total savings",
+        n_months,
+        format!("{:.02}", savings.balance + savings.overflow_capital)
+    ]);
+    if kind == AccountKind::Pel || kind == AccountKind::Cel {
+        let plan = HomeSavingsPlan::new(account.clone(), deposit)?;
+        savings_table.add_row(row![
+            "tax on interest",
+            n_months,
+            format!("{:.02}", plan.tax_on_interest(n_months)?)
+        ]);
+        if let Some(years) = parse_optional_value(matches, ARG_LOAN_YEARS)? {
+            savings_table.add_row(row![
+                "loan rate (% by year)",
+                n_months,
+                format!("{:.02}", plan.loan_rate(n_months) * 100_f32)
+            ]);
+            savings_table.add_row(row![
+                format!("loan entitlement on {} years", years),
+                n_months,
+                plan.max_loan(n_months, years)?
+            ]);
+        }
+    }
+    savings_table.printstd();
+    Ok(())
+}
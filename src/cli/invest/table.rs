@@ -1,6 +1,10 @@
 use super::{common_invest_args, parse_common_invest_args};
 use clap::{App, Arg, ArgMatches, SubCommand};
-use investment::Investment;
+use cli::inflation::{parse_money, Money};
+use cli::parse_value;
+use error::{check_duration, HomeCalcError};
+use fees::Fees;
+use investment::{Investment, ScheduleRow};
 use prettytable::row::Row;
 
 pub const SUB_INVEST_TABLE: &str = "table";
@@ -28,44 +32,120 @@ pub fn invest_table_subcommand<'a, 'b>() -> App<'a, 'b> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_invest_table<'a>(matches: &ArgMatches<'a>) {
-    let invest = parse_common_invest_args(matches);
-    let every = matches
-        .value_of(ARG_EVERY_PERIOD)
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
-    let to = matches.value_of(ARG_TO).unwrap().parse::<u32>().unwrap();
+pub fn execute_invest_table<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let invest = parse_common_invest_args(matches)?;
+    let every = check_duration(ARG_EVERY_PERIOD, parse_value(matches, ARG_EVERY_PERIOD)?)?;
+    let to = parse_value(matches, ARG_TO)?;
+    let money = parse_money(matches, invest.periodicity)?;
 
     println!("*** For an investment of {} and regular additions of {} per period at a interest rate of {}% per year\n",
             invest.capital, invest.regular_addition, invest.yield_rate * 100_f32);
+    if let Some(ref curve) = invest.curve {
+        println!("*** The yield rate changes over time: {} ***\n", curve);
+    }
+    if let Some(ref portfolio) = invest.portfolio {
+        println!("*** The capital is spread over a portfolio: {} ***\n", portfolio);
+    }
+    if !invest.plan.is_constant() {
+        println!(
+            "*** The regular additions follow a plan: {}% more each year, {} changes, \
+             {} pauses and {} lump sums ***\n",
+            invest.plan.step_up * 100_f32,
+            invest.plan.changes.len(),
+            invest.plan.pauses.len(),
+            invest.plan.lump_sums.len()
+        );
+    }
+    money.print_assumption();
 
     let mut invest_table = table!([
         "At (periods)",
         "At (~years)",
+        "Addition",
         "Total additions",
         "Total invest",
+        "Withdrawal",
+        "Total withdrawals",
         "Capital",
         "interest earned",
+        "Total fees",
+        "Gross - net",
+        "After tax",
     ]);
-    invest_table.add_row(get_row(&invest, 0));
-    for at in (every..to).step_by(every as usize) {
-        invest_table.add_row(get_row(&invest, at));
+    let capital = invest.capital as f64;
+    let start = ScheduleRow {
+        period: 0,
+        opening: capital,
+        contribution: 0_f64,
+        growth: 0_f64,
+        fees: 0_f64,
+        withdrawal: 0_f64,
+        tax: 0_f64,
+        closing: capital,
+        after_tax: capital,
+    };
+    let mut totals = Totals::default();
+    invest_table.add_row(get_row(&invest, &money, &totals, &start, capital));
+    let gross = Investment {
+        fees: Fees::default(),
+        ..invest.clone()
+    };
+    for (row, gross_row) in invest.schedule().zip(gross.schedule()).take(to as usize) {
+        totals.add(&money, &row);
+        if row.period.is_multiple_of(every) || row.period == to {
+            invest_table.add_row(get_row(&invest, &money, &totals, &row, gross_row.closing));
+        }
     }
-    invest_table.add_row(get_row(&invest, to));
     invest_table.printstd();
+    if let Some(period) = invest.depletion_period(to) {
+        println!(
+            "\n*** The capital is depleted at period {} (~{:.1} years) ***",
+            period,
+            period as f32 / invest.periodicity as f32
+        );
+    }
+    Ok(())
+}
+
+/// The totals of the flows of an investment since its start
+#[derive(Default)]
+struct Totals {
+    additions: f64,
+    withdrawals: f64,
+    fees: f64,
+}
+
+impl Totals {
+    fn add(&mut self, money: &Money, row: &ScheduleRow) {
+        self.additions += money.value(row.contribution, row.period);
+        self.withdrawals += money.value(row.withdrawal, row.period);
+        self.fees += money.value(row.fees, row.period);
+    }
 }
 
-fn get_row(invest: &Investment, at: u32) -> Row {
+fn get_row(
+    invest: &Investment,
+    money: &Money,
+    totals: &Totals,
+    row: &ScheduleRow,
+    gross_capital: f64,
+) -> Row {
+    let at = row.period;
     let years_round = format!("{:.1}", at as f32 / invest.periodicity as f32);
-    let capital_at = invest.capital_at(at);
-    let total_invest = invest.capital + invest.additions_total(at);
+    let capital_at = money.value(row.closing, at);
+    let total_invest = invest.capital as f64 + totals.additions;
     row![
         at,
         years_round,
-        format!("{:.2}", invest.additions_total(at)),
+        format!("{:.2}", money.value(row.contribution, at)),
+        format!("{:.2}", totals.additions),
         format!("{:.2}", total_invest),
+        format!("{:.2}", money.value(row.withdrawal, at)),
+        format!("{:.2}", totals.withdrawals),
         format!("{:.2}", capital_at),
-        format!("{:.2}", capital_at - total_invest as f64),
+        format!("{:.2}", capital_at + totals.withdrawals - total_invest),
+        format!("{:.2}", totals.fees),
+        format!("{:.2}", money.value(gross_capital, at) - capital_at),
+        format!("{:.2}", money.value(row.after_tax, at)),
     ]
 }
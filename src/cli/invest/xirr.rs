@@ -0,0 +1,61 @@
+use cash_flow::{load_flows, xirr, xnpv};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{or_none, parse_optional_value};
+use error::HomeCalcError;
+use std::path::Path;
+
+pub const SUB_INVEST_XIRR: &str = "xirr";
+const ARG_FILE: &str = "file";
+const ARG_DISCOUNT_RATE: &str = "discount-rate";
+
+/// Returns the invest xirr sub command
+pub fn invest_xirr_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_INVEST_XIRR)
+        .about("compute the return by year of dated cash flows")
+        .arg(
+            Arg::with_name(ARG_FILE)
+                .takes_value(true)
+                .required(true)
+                .index(1)
+                .help("CSV file of cash flows (date;amount), contributions being negative"),
+        ).arg(
+            Arg::with_name(ARG_DISCOUNT_RATE)
+                .long(ARG_DISCOUNT_RATE)
+                .short("r")
+                .takes_value(true)
+                .required(false)
+                .help("discount rate by year in percent for the net present value"),
+        )
+}
+
+/// Execute the work and print results for the xirr sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_invest_xirr<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let flows = load_flows(Path::new(matches.value_of(ARG_FILE).unwrap_or("")))?;
+    let (outflows, inflows) = flows.iter().fold((0_f64, 0_f64), |(out, inc), &(_, flow)| {
+        if flow < 0_f64 {
+            (out - flow, inc)
+        } else {
+            (out, inc + flow)
+        }
+    });
+
+    println!("*** For {} cash flows ***\n", flows.len());
+    let mut flows_table = table!(["title", "value"]);
+    flows_table.add_row(row!["total paid", format!("{:.02}", outflows)]);
+    flows_table.add_row(row!["total received", format!("{:.02}", inflows)]);
+    flows_table.add_row(row![
+        "return (% by year)",
+        or_none(xirr(&flows).map(|rate| format!("{:.02}", rate * 100_f64)))?
+    ]);
+    if let Some(rate) = parse_optional_value::<f64>(matches, ARG_DISCOUNT_RATE)? {
+        flows_table.add_row(row![
+            format!("net present value at {}%", rate),
+            format!("{:.02}", xnpv(rate / 100_f64, &flows))
+        ]);
+    }
+    flows_table.printstd();
+    Ok(())
+}
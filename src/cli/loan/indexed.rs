@@ -0,0 +1,143 @@
+use super::{common_loan_args, parse_common_loan_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{parse_optional_value, parse_percent, parse_value};
+use error::HomeCalcError;
+use indexed_loan::{load_rate_index, IndexedLoan};
+use std::path::Path;
+
+pub const SUB_LOAN_INDEXED: &str = "indexed";
+const ARG_INDEX_FILE: &str = "index-file";
+const ARG_START: &str = "start";
+const ARG_MARGIN: &str = "margin";
+const ARG_RESET_EVERY: &str = "reset-every";
+const ARG_CAP: &str = "cap";
+
+/// Returns the loan indexed sub command
+pub fn loan_indexed_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_LOAN_INDEXED)
+        .about("compute the installments of a loan indexed on a reference rate CSV (date;rate)")
+        .arg(
+            Arg::with_name(ARG_INDEX_FILE)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).args(indexed_loan_args().as_slice())
+        .args(common_loan_args().as_slice())
+}
+
+/// Return the arguments describing the indexation of a loan
+pub fn indexed_loan_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name(ARG_START)
+            .long(ARG_START)
+            .short("s")
+            .takes_value(true)
+            .required(true)
+            .help("date the funds are released (YYYY-MM-DD)"),
+        Arg::with_name(ARG_MARGIN)
+            .long(ARG_MARGIN)
+            .short("m")
+            .takes_value(true)
+            .required(true)
+            .help("margin of the bank added to the index in percent"),
+        Arg::with_name(ARG_RESET_EVERY)
+            .long(ARG_RESET_EVERY)
+            .short("e")
+            .takes_value(true)
+            .required(false)
+            .default_value("12")
+            .help("number of months between rate resets"),
+        Arg::with_name(ARG_CAP)
+            .long(ARG_CAP)
+            .short("k")
+            .takes_value(true)
+            .required(false)
+            .help("maximum move of the rate from the initial rate in percent"),
+    ]
+}
+
+/// Return an indexed loan from cli arguments
+///
+/// Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn parse_indexed_loan_args<'a>(
+    matches: &ArgMatches<'a>,
+) -> Result<IndexedLoan, HomeCalcError> {
+    IndexedLoan::new(
+        parse_common_loan_args(matches)?,
+        parse_value(matches, ARG_START)?,
+        parse_percent(matches, ARG_MARGIN)?,
+        parse_value(matches, ARG_RESET_EVERY)?,
+        parse_optional_value::<f32>(matches, ARG_CAP)?.map(|cap| cap / 100_f32),
+    )
+}
+
+/// Execute the work and print results for the indexed sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_loan_indexed<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let indexed = parse_indexed_loan_args(matches)?;
+    let index = load_rate_index(Path::new(matches.value_of(ARG_INDEX_FILE).unwrap()))?;
+    let installments = indexed.installments(&index);
+
+    println!(
+        "*** Information for a loan of {} during {} years with period of {}, \
+         index + {}% reset every {} months from an initial rate of {}% ***",
+        indexed.loan.capital,
+        indexed.loan.years,
+        indexed.loan.period,
+        indexed.margin * 100_f32,
+        indexed.reset_every,
+        indexed.loan.interest_rate_year * 100_f32
+    );
+    if let Some(last) = index.last_date() {
+        println!("*** Index known up to {}, extrapolated flat forward after (*) ***\n", last);
+    }
+    let mut loan_table = table!([
+        "#",
+        "Due",
+        "Rate",
+        "Term price",
+        "Interest",
+        "Capital",
+        "Ending balance",
+    ]);
+    for installment in installments.iter().filter(|i| {
+        (i.number - 1) % indexed.reset_terms() == 0 || i.number == indexed.loan.periods()
+    }) {
+        loan_table.add_row(row![
+            installment.number,
+            installment.due,
+            format!(
+                "{:.3}%{}",
+                installment.interest_rate_year * 100_f32,
+                if installment.extrapolated { " *" } else { "" }
+            ),
+            format!("{:.2}", installment.term_price),
+            format!("{:.2}", installment.interest),
+            format!("{:.2}", installment.capital),
+            format!("{:.2}", installment.balance),
+        ]);
+    }
+    loan_table.printstd();
+
+    let total_interest: f64 = installments.iter().map(|i| i.interest).sum();
+    let worst = installments
+        .iter()
+        .map(|i| i.term_price)
+        .fold(0_f64, f64::max);
+    let mut summary_table = table!(["title", "value"]);
+    summary_table.add_row(row![
+        "initial term price",
+        format!("{:.2}", indexed.loan.term_price())
+    ]);
+    summary_table.add_row(row!["highest term price", format!("{:.2}", worst)]);
+    summary_table.add_row(row!["total interest", format!("{:.2}", total_interest)]);
+    summary_table.add_row(row![
+        "total interest at the initial rate",
+        format!("{:.2}", indexed.loan.interest_at(indexed.loan.periods())?)
+    ]);
+    summary_table.printstd();
+    Ok(())
+}
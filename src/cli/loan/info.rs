@@ -1,5 +1,9 @@
 use super::{common_loan_args, parse_common_loan_args};
+use cash_flow::{irr, yearly_rate};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::inflation::parse_money;
+use cli::{or_none, parse_value};
+use error::HomeCalcError;
 
 pub const SUB_LOAN_INFO_AT: &str = "info-at";
 const ARG_N_PERIOD: &str = "n-period";
@@ -20,13 +24,10 @@ pub fn loan_info_subcommand<'a, 'b>() -> App<'a, 'b> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_loan_info_at<'a>(matches: &ArgMatches<'a>) {
-    let loan = parse_common_loan_args(matches);
-    let at = matches
-        .value_of(ARG_N_PERIOD)
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
+pub fn execute_loan_info_at<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let loan = parse_common_loan_args(matches)?;
+    let at: u32 = parse_value(matches, ARG_N_PERIOD)?;
+    let money = parse_money(matches, loan.period)?;
 
     println!(
         "*** Information for a loan of {} during {} years with period of {} at {}% ***\n",
@@ -35,11 +36,35 @@ pub fn execute_loan_info_at<'a>(matches: &ArgMatches<'a>) {
         loan.period,
         loan.interest_rate_year * 100_f32
     );
+    money.print_assumption();
+    let capital_paid = money.total(
+        loan.capital_at(at)?,
+        |period| Ok(loan.capital_at(period)? - loan.capital_at(period - 1)?),
+        at,
+    )?;
+    let paid = money.total(
+        loan.paid(at)?,
+        |period| Ok(loan.paid(period)? - loan.paid(period - 1)?),
+        at,
+    )?;
+    let interest_paid = money.total(
+        loan.interest_at(at)?,
+        |period| Ok(loan.interest_at(period)? - loan.interest_at(period - 1)?),
+        at,
+    )?;
     let years_round = format!("{:.1}", at as f32 / loan.period as f32);
     let mut loan_table = table!(["title", "at (periods)", "at (~years)", "value"]);
-    loan_table.add_row(row!["term price", "NONE", "NONE", loan.term_price()]);
-    loan_table.add_row(row!["capital paid", at, years_round, loan.capital_at(at)]);
-    loan_table.add_row(row!["paid", at, years_round, loan.paid(at)]);
-    loan_table.add_row(row!["interest paid", at, years_round, loan.interest_at(at)]);
+    loan_table.add_row(row!["term price", "NONE", "NONE", money.value(loan.term_price(), at)]);
+    loan_table.add_row(row!["capital paid", at, years_round, capital_paid]);
+    loan_table.add_row(row!["paid", at, years_round, paid]);
+    loan_table.add_row(row!["interest paid", at, years_round, interest_paid]);
+    let rate = irr(&loan.cash_flows()).map(|rate| yearly_rate(rate, loan.period) * 100_f64);
+    loan_table.add_row(row![
+        "effective rate (% by year)",
+        "NONE",
+        "NONE",
+        or_none(rate.map(|rate| format!("{:.03}", rate)))?
+    ]);
     loan_table.printstd();
+    Ok(())
 }
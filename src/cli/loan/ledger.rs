@@ -0,0 +1,161 @@
+use super::{common_loan_args, parse_common_loan_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::{parse_optional_value, parse_value};
+use error::HomeCalcError;
+use ledger::{load_payments, InstallmentStatus, Ledger};
+use std::path::Path;
+
+pub const SUB_LOAN_LEDGER: &str = "ledger";
+const ARG_PAYMENTS_FILE: &str = "payments-file";
+const ARG_FIRST_DUE: &str = "first-due";
+const ARG_AS_OF: &str = "as-of";
+
+/// Returns the loan ledger sub command
+pub fn loan_ledger_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_LOAN_LEDGER)
+        .about("compare actual payments from a bank export (CSV or OFX) to the loan schedule")
+        .arg(
+            Arg::with_name(ARG_PAYMENTS_FILE)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_FIRST_DUE)
+                .long(ARG_FIRST_DUE)
+                .short("f")
+                .takes_value(true)
+                .required(true)
+                .help("due date of the first installment (YYYY-MM-DD)"),
+        ).arg(
+            Arg::with_name(ARG_AS_OF)
+                .long(ARG_AS_OF)
+                .short("a")
+                .takes_value(true)
+                .required(false)
+                .help("reconcile installments due up to this date (default: last payment)"),
+        ).args(common_loan_args().as_slice())
+}
+
+/// Execute the work and print results for the ledger sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_loan_ledger<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let loan = parse_common_loan_args(matches)?;
+    let first_due = parse_value(matches, ARG_FIRST_DUE)?;
+    let payments = load_payments(Path::new(matches.value_of(ARG_PAYMENTS_FILE).unwrap()))?;
+    let as_of = match parse_optional_value(matches, ARG_AS_OF)? {
+        Some(as_of) => as_of,
+        None => payments
+            .iter()
+            .map(|payment| payment.date)
+            .max()
+            .unwrap_or(first_due),
+    };
+
+    println!(
+        "*** Ledger for a loan of {} during {} years with period of {} at {}% as of {} ***\n",
+        loan.capital,
+        loan.years,
+        loan.period,
+        loan.interest_rate_year * 100_f32,
+        as_of
+    );
+    let ledger = Ledger::new(loan, first_due, payments);
+    let records = ledger.reconcile(&as_of)?;
+    let mut ledger_table = table!([
+        "#",
+        "Due",
+        "Expected",
+        "Paid",
+        "Status",
+        "Planned balance",
+        "Actual balance",
+    ]);
+    for record in &records {
+        let status = match record.status {
+            InstallmentStatus::Paid => "paid".to_string(),
+            InstallmentStatus::Missed => "MISSED".to_string(),
+            InstallmentStatus::Partial => {
+                format!("partial (-{:.2})", record.expected - record.paid)
+            }
+            InstallmentStatus::Prepaid => format!("prepaid (+{:.2})", record.prepayment()),
+        };
+        ledger_table.add_row(row![
+            record.number,
+            record.due,
+            format!("{:.2}", record.expected),
+            format!("{:.2}", record.paid),
+            status,
+            format!("{:.2}", record.planned_balance),
+            format!("{:.2}", record.actual_balance),
+        ]);
+    }
+    ledger_table.printstd();
+
+    let missed = records
+        .iter()
+        .filter(|r| r.status == InstallmentStatus::Missed)
+        .count();
+    let extra_payments: usize = records
+        .iter()
+        .filter(|r| r.payments > 1)
+        .map(|r| r.payments - 1)
+        .sum();
+    let prepaid: f64 = records.iter().map(|r| r.prepayment()).sum();
+    let projection = ledger.projection(&as_of)?;
+    let mut summary_table = table!(["title", "value"]);
+    summary_table.add_row(row!["missed installments", missed]);
+    summary_table.add_row(row!["extra payments", extra_payments]);
+    summary_table.add_row(row!["prepaid capital", format!("{:.2}", prepaid)]);
+    if let Some(last) = records.last() {
+        summary_table.add_row(row![
+            "balance gap (actual - planned)",
+            format!("{:.2}", last.actual_balance - last.planned_balance)
+        ]);
+    }
+    summary_table.add_row(row!["remaining installments", projection.len()]);
+    if let Some(next) = projection.first() {
+        summary_table.add_row(row![
+            "projected term price",
+            format!("{:.2}", next.term_price)
+        ]);
+        summary_table.add_row(row![
+            "projected remaining interest",
+            format!("{:.2}", projection.iter().map(|p| p.interest).sum::<f64>())
+        ]);
+        summary_table.add_row(row![
+            "projected end",
+            projection.last().unwrap().due
+        ]);
+    }
+    summary_table.printstd();
+
+    if !projection.is_empty() {
+        println!("\n*** Projected remaining schedule from the actual balance ***\n");
+        let mut projection_table = table!([
+            "#",
+            "Due",
+            "Term price",
+            "Interest",
+            "Capital",
+            "Ending balance",
+        ]);
+        let every = ledger.loan.period as u32;
+        for projected in projection
+            .iter()
+            .filter(|p| p.number % every == 0 || p.number == ledger.loan.periods())
+        {
+            projection_table.add_row(row![
+                projected.number,
+                projected.due,
+                format!("{:.2}", projected.term_price),
+                format!("{:.2}", projected.interest),
+                format!("{:.2}", projected.capital),
+                format!("{:.2}", projected.balance),
+            ]);
+        }
+        projection_table.printstd();
+    }
+    Ok(())
+}
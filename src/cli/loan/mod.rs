@@ -1,12 +1,26 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
 
+/// The indexed module which contains the subcommand for indexed sub command
+mod indexed;
 /// The info module which contains the subcommand for info-at sub command
 mod info;
+/// The ledger module which contains the subcommand for ledger sub command
+mod ledger;
+/// The offers module which contains the subcommand for offers sub command
+mod offers;
+/// The stress module which contains the subcommand for stress sub command
+mod stress;
 /// The table module which contains the subcommand for table sub command
 mod table;
 
+use self::indexed::{execute_loan_indexed, loan_indexed_subcommand, SUB_LOAN_INDEXED};
 use self::info::{execute_loan_info_at, loan_info_subcommand, SUB_LOAN_INFO_AT};
+use self::ledger::{execute_loan_ledger, loan_ledger_subcommand, SUB_LOAN_LEDGER};
+use self::offers::{execute_loan_offers, loan_offers_subcommand, SUB_LOAN_OFFERS};
+use self::stress::{execute_loan_stress, loan_stress_subcommand, SUB_LOAN_STRESS};
 use self::table::{execute_loan_table, loan_table_subcommand, SUB_LOAN_TABLE};
+use cli::{parse_percent, parse_value};
+use error::HomeCalcError;
 use loan::Loan;
 
 /// The loan sub command string
@@ -19,7 +33,14 @@ const ARG_CAPITAL: &str = "capital";
 
 /// Returns the loan sub command
 pub fn loan_sub_command<'a, 'b, 'c>() -> Vec<App<'a, 'b>> {
-    let loan_sub_commands = vec![loan_info_subcommand(), loan_table_subcommand()];
+    let loan_sub_commands = vec![
+        loan_info_subcommand(),
+        loan_table_subcommand(),
+        loan_ledger_subcommand(),
+        loan_offers_subcommand(),
+        loan_indexed_subcommand(),
+        loan_stress_subcommand(),
+    ];
     let sub_commands = vec![SubCommand::with_name(SUB_LOAN).subcommands(loan_sub_commands)];
     sub_commands
 }
@@ -28,11 +49,18 @@ pub fn loan_sub_command<'a, 'b, 'c>() -> Vec<App<'a, 'b>> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_loan_sub_command<'a>(matches: &ArgMatches<'a>) {
+pub fn execute_loan_sub_command<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
     match matches.subcommand() {
         (SUB_LOAN_INFO_AT, Some(info_at_matches)) => execute_loan_info_at(info_at_matches),
         (SUB_LOAN_TABLE, Some(table_matches)) => execute_loan_table(table_matches),
-        _ => println!("*** No command found"),
+        (SUB_LOAN_LEDGER, Some(ledger_matches)) => execute_loan_ledger(ledger_matches),
+        (SUB_LOAN_OFFERS, Some(offers_matches)) => execute_loan_offers(offers_matches),
+        (SUB_LOAN_INDEXED, Some(indexed_matches)) => execute_loan_indexed(indexed_matches),
+        (SUB_LOAN_STRESS, Some(stress_matches)) => execute_loan_stress(stress_matches),
+        _ => {
+            println!("*** No command found");
+            Ok(())
+        }
     }
 }
 
@@ -70,24 +98,11 @@ pub fn common_loan_args<'a, 'b, 'c>() -> Vec<Arg<'b, 'c>> {
 ///
 /// Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn parse_common_loan_args<'a>(matches: &ArgMatches<'a>) -> Loan {
+pub fn parse_common_loan_args<'a>(matches: &ArgMatches<'a>) -> Result<Loan, HomeCalcError> {
     Loan::new(
-        matches.value_of(ARG_YEARS).unwrap().parse::<u8>().unwrap(),
-        matches
-            .value_of(ARG_PERIODICITY)
-            .unwrap()
-            .parse::<u8>()
-            .unwrap(),
-        matches
-            .value_of(ARG_INTEREST_RATE)
-            .unwrap()
-            .parse::<f32>()
-            .unwrap()
-            / 100_f32,
-        matches
-            .value_of(ARG_CAPITAL)
-            .unwrap()
-            .parse::<u32>()
-            .unwrap(),
+        parse_value(matches, ARG_YEARS)?,
+        parse_value(matches, ARG_PERIODICITY)?,
+        parse_percent(matches, ARG_INTEREST_RATE)?,
+        parse_value(matches, ARG_CAPITAL)?,
     )
 }
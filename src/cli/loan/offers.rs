@@ -0,0 +1,115 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::parse_optional_value;
+use comparators::PERIODICITY;
+use error::HomeCalcError;
+use offers::{load_offers, rank_offers};
+use prettytable::cell::Cell;
+use prettytable::row::Row;
+use std::path::Path;
+
+pub const SUB_LOAN_OFFERS: &str = "offers";
+const ARG_OFFERS_FILE: &str = "offers-file";
+const ARG_HORIZONS: &str = "horizons";
+const ARG_RANK_AT: &str = "rank-at";
+
+/// Returns the loan offers sub command
+pub fn loan_offers_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_LOAN_OFFERS)
+        .about(
+            "rank bank offers from a CSV file (name;capital;years;rate;insurance;fees;guarantee)",
+        ).arg(
+            Arg::with_name(ARG_OFFERS_FILE)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_HORIZONS)
+                .long(ARG_HORIZONS)
+                .short("H")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .default_value("7")
+                .help("horizons in years (e.g. resale) for the cost of the credit"),
+        ).arg(
+            Arg::with_name(ARG_RANK_AT)
+                .long(ARG_RANK_AT)
+                .short("r")
+                .takes_value(true)
+                .required(false)
+                .help("rank on the cost at this horizon in years instead of the total cost"),
+        )
+}
+
+/// Execute the work and print results for the offers sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_loan_offers<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let offers = load_offers(Path::new(matches.value_of(ARG_OFFERS_FILE).unwrap()))?;
+    let horizons = matches
+        .values_of(ARG_HORIZONS)
+        .unwrap()
+        .map(|horizon| {
+            horizon
+                .parse::<u32>()
+                .map_err(|_| HomeCalcError::InvalidValue {
+                    name: ARG_HORIZONS.to_string(),
+                    value: horizon.to_string(),
+                })
+        }).collect::<Result<Vec<u32>, HomeCalcError>>()?;
+    let rank_at =
+        parse_optional_value::<u32>(matches, ARG_RANK_AT)?.map(|years| years * PERIODICITY as u32);
+    let offers = rank_offers(offers, rank_at)?;
+
+    match rank_at {
+        Some(rank_at) => println!(
+            "*** {} offers ranked on the cost of the credit at {} years ***\n",
+            offers.len(),
+            rank_at / PERIODICITY as u32
+        ),
+        None => println!(
+            "*** {} offers ranked on the total cost of the credit ***\n",
+            offers.len()
+        ),
+    }
+    let mut titles = row![
+        "Rank",
+        "Offer",
+        "Capital",
+        "Years",
+        "Rate",
+        "Monthly payment",
+        "APR",
+        "Total cost",
+    ];
+    for horizon in &horizons {
+        titles.add_cell(Cell::new(&format!("Cost at {}y", horizon)));
+    }
+    let mut offers_table = table!();
+    offers_table.set_titles(titles);
+    for (rank, offer) in offers.iter().enumerate() {
+        let mut cells = vec![
+            (rank + 1).to_string(),
+            offer.name.clone(),
+            offer.loan.capital.to_string(),
+            offer.loan.years.to_string(),
+            format!("{:.2}%", offer.loan.interest_rate_year * 100_f32),
+            format!("{:.2}", offer.monthly_payment()),
+            match offer.apr() {
+                Ok(apr) => format!("{:.2}%", apr * 100_f64),
+                Err(_) => "NONE".to_string(),
+            },
+            format!("{:.2}", offer.total_cost()?),
+        ];
+        for horizon in &horizons {
+            cells.push(format!(
+                "{:.2}",
+                offer.cost_at(horizon * PERIODICITY as u32)?
+            ));
+        }
+        offers_table.add_row(Row::new(cells.iter().map(|c| Cell::new(c)).collect()));
+    }
+    offers_table.printstd();
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use super::common_loan_args;
+use super::indexed::{indexed_loan_args, parse_indexed_loan_args};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::parse_optional_value;
+use cli::stress::{parse_stress_args, print_stress_results, stress_args};
+use date::Date;
+use error::HomeCalcError;
+use indexed_loan::load_rate_index;
+use std::path::Path;
+use stress::{default_shocks, stress_indexed_loan, Shock};
+
+pub const SUB_LOAN_STRESS: &str = "stress";
+const ARG_INDEX_FILE: &str = "index-file";
+const ARG_AS_OF: &str = "as-of";
+
+/// Returns the loan stress sub command
+pub fn loan_stress_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(SUB_LOAN_STRESS)
+        .about("apply rate shocks to a loan indexed on a reference rate CSV (date;rate)")
+        .arg(
+            Arg::with_name(ARG_INDEX_FILE)
+                .takes_value(true)
+                .required(true)
+                .index(1),
+        ).arg(
+            Arg::with_name(ARG_AS_OF)
+                .long(ARG_AS_OF)
+                .short("a")
+                .takes_value(true)
+                .required(false)
+                .help("date of the stress test, the shocks start after it (default: today)"),
+        ).args(stress_args().as_slice())
+        .args(indexed_loan_args().as_slice())
+        .args(common_loan_args().as_slice())
+}
+
+/// Execute the work and print results for the stress sub command
+///
+/// # Arguments
+/// * `matches` - The command matches to retrieve the paramters
+pub fn execute_loan_stress<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let indexed = parse_indexed_loan_args(matches)?;
+    let index = load_rate_index(Path::new(matches.value_of(ARG_INDEX_FILE).unwrap()))?;
+    let (income, threshold) = parse_stress_args(matches)?;
+    let as_of = parse_optional_value(matches, ARG_AS_OF)?.unwrap_or_else(Date::today);
+    let mut shocks = vec![Shock::Parallel(0_f32)];
+    shocks.extend(default_shocks());
+
+    println!(
+        "*** Stress test for a loan of {} during {} years, index + {}% reset every {} months \
+         as of {} ***",
+        indexed.loan.capital,
+        indexed.loan.years,
+        indexed.margin * 100_f32,
+        indexed.reset_every,
+        as_of
+    );
+    let results = stress_indexed_loan(&indexed, &index, &shocks, &as_of, income, threshold)?;
+    print_stress_results(&results, threshold);
+    Ok(())
+}
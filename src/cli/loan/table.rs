@@ -1,5 +1,8 @@
 use super::{common_loan_args, parse_common_loan_args};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use cli::inflation::{parse_money, Money};
+use cli::parse_value;
+use error::{check_duration, HomeCalcError};
 use loan::Loan;
 use prettytable::row::Row;
 
@@ -22,13 +25,10 @@ pub fn loan_table_subcommand<'a, 'b>() -> App<'a, 'b> {
 ///
 /// # Arguments
 /// * `matches` - The command matches to retrieve the paramters
-pub fn execute_loan_table<'a>(matches: &ArgMatches<'a>) {
-    let loan = parse_common_loan_args(matches);
-    let every = matches
-        .value_of(ARG_EVERY_PERIOD)
-        .unwrap()
-        .parse::<u32>()
-        .unwrap();
+pub fn execute_loan_table<'a>(matches: &ArgMatches<'a>) -> Result<(), HomeCalcError> {
+    let loan = parse_common_loan_args(matches)?;
+    let every = check_duration(ARG_EVERY_PERIOD, parse_value(matches, ARG_EVERY_PERIOD)?)?;
+    let money = parse_money(matches, loan.period)?;
 
     println!(
         "*** Information for a loan of {} during {} years with period of {} at {}% ***\n",
@@ -37,6 +37,7 @@ pub fn execute_loan_table<'a>(matches: &ArgMatches<'a>) {
         loan.period,
         loan.interest_rate_year * 100_f32
     );
+    money.print_assumption();
 
     let mut loan_table = table!([
         "At (periods)",
@@ -46,24 +47,33 @@ pub fn execute_loan_table<'a>(matches: &ArgMatches<'a>) {
         "Total interest",
         "~Interest overhead ratio",
     ]);
-    loan_table.add_row(get_row(&loan, 1));
+    loan_table.add_row(get_row(&loan, &money, 1)?);
     for at in (every..(loan.years as u32 * loan.period as u32)).step_by(every as usize) {
-        loan_table.add_row(get_row(&loan, at));
+        loan_table.add_row(get_row(&loan, &money, at)?);
     }
-    loan_table.add_row(get_row(&loan, loan.years as u32 * loan.period as u32));
+    loan_table.add_row(get_row(&loan, &money, loan.years as u32 * loan.period as u32)?);
     loan_table.printstd();
+    Ok(())
 }
 
-fn get_row(loan: &Loan, at: u32) -> Row {
+fn get_row(loan: &Loan, money: &Money, at: u32) -> Result<Row, HomeCalcError> {
     let years_round = format!("{:.1}", at as f32 / loan.period as f32);
-    let capital_paid = loan.capital_at(at);
-    let interest_paid = loan.interest_at(at);
-    row![
+    let capital_paid = money.total(
+        loan.capital_at(at)?,
+        |period| Ok(loan.capital_at(period)? - loan.capital_at(period - 1)?),
+        at,
+    )?;
+    let interest_paid = money.total(
+        loan.interest_at(at)?,
+        |period| Ok(loan.interest_at(period)? - loan.interest_at(period - 1)?),
+        at,
+    )?;
+    Ok(row![
         at,
         years_round,
-        format!("{:.2}", loan.capital as f64 - capital_paid),
+        format!("{:.2}", money.value(loan.balance_at(at)?, at)),
         format!("{:.2}", capital_paid),
         format!("{:.2}", interest_paid),
         format!("{:.2}%", (interest_paid / capital_paid) * 100_f64),
-    ]
+    ])
 }
@@ -6,3 +6,93 @@ pub mod invest;
 
 /// The home module which contains all home sub commands
 pub mod home;
+
+/// The inflation module which contains the global arguments for real amounts
+pub mod inflation;
+
+/// The stress module which contains the arguments and output shared by stress sub commands
+pub mod stress;
+
+use clap::ArgMatches;
+use error::HomeCalcError;
+use std::str::FromStr;
+
+/// Parse the value of an argument
+///
+/// # Arguments
+/// * `matches` - cli arguments matches
+/// * `name` - name of the argument
+pub fn parse_value<'a, T: FromStr>(
+    matches: &ArgMatches<'a>,
+    name: &str,
+) -> Result<T, HomeCalcError> {
+    let value = matches.value_of(name).unwrap_or("");
+    value.parse::<T>().map_err(|_| HomeCalcError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+/// Parse the value of an optional argument
+///
+/// # Arguments
+/// * `matches` - cli arguments matches
+/// * `name` - name of the argument
+pub fn parse_optional_value<'a, T: FromStr>(
+    matches: &ArgMatches<'a>,
+    name: &str,
+) -> Result<Option<T>, HomeCalcError> {
+    match matches.value_of(name) {
+        Some(_) => parse_value(matches, name).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Parse the value of an argument given in percent
+///
+/// # Arguments
+/// * `matches` - cli arguments matches
+/// * `name` - name of the argument
+pub fn parse_percent<'a>(matches: &ArgMatches<'a>, name: &str) -> Result<f32, HomeCalcError> {
+    Ok(parse_value::<f32>(matches, name)? / 100_f32)
+}
+
+/// Parse the values of an argument given as pairs of numbers, e.g. `12:5000`
+///
+/// # Arguments
+/// * `matches` - cli arguments matches
+/// * `name` - name of the argument
+/// * `separator` - the separator between the two numbers of a pair
+pub fn parse_pairs<'a>(
+    matches: &ArgMatches<'a>,
+    name: &str,
+    separator: char,
+) -> Result<Vec<(u32, u32)>, HomeCalcError> {
+    let values = match matches.values_of(name) {
+        Some(values) => values,
+        None => return Ok(Vec::new()),
+    };
+    values
+        .map(|value| {
+            let mut numbers = value.splitn(2, separator).map(|n| n.trim().parse::<u32>());
+            match (numbers.next(), numbers.next()) {
+                (Some(Ok(first)), Some(Ok(second))) => Ok((first, second)),
+                _ => Err(HomeCalcError::InvalidValue {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                }),
+            }
+        }).collect()
+}
+
+/// Return a value to print, or NONE when there is no solution
+///
+/// # Arguments
+/// * `value` - the value or the error of a computation
+pub fn or_none<T: ToString>(value: Result<T, HomeCalcError>) -> Result<String, HomeCalcError> {
+    match value {
+        Ok(value) => Ok(value.to_string()),
+        Err(HomeCalcError::NoSolution(_)) => Ok("NONE".to_string()),
+        Err(error) => Err(error),
+    }
+}
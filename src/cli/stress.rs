@@ -0,0 +1,71 @@
+use clap::{Arg, ArgMatches};
+use cli::{parse_optional_value, parse_value};
+use error::{check_rate, HomeCalcError};
+use stress::{check_income, StressResult, DEBT_RATIO_THRESHOLD};
+
+const ARG_INCOME: &str = "income";
+const ARG_THRESHOLD: &str = "threshold";
+
+/// Return the arguments shared by stress sub commands
+pub fn stress_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name(ARG_INCOME)
+            .long(ARG_INCOME)
+            .short("n")
+            .takes_value(true)
+            .required(true)
+            .help("net income of the borrowers by month"),
+        Arg::with_name(ARG_THRESHOLD)
+            .long(ARG_THRESHOLD)
+            .short("t")
+            .takes_value(true)
+            .required(false)
+            .help("maximum debt ratio in percent (default: 35)"),
+    ]
+}
+
+/// Parse the monthly income and the debt ratio threshold from the cli
+///
+/// # Arguments
+/// *  `matches` - cli arguments matches
+pub fn parse_stress_args<'a>(matches: &ArgMatches<'a>) -> Result<(f64, f64), HomeCalcError> {
+    let threshold = match parse_optional_value::<f64>(matches, ARG_THRESHOLD)? {
+        Some(threshold) => {
+            check_rate("debt ratio threshold", (threshold / 100_f64) as f32)?;
+            threshold / 100_f64
+        }
+        None => DEBT_RATIO_THRESHOLD,
+    };
+    Ok((check_income(parse_value(matches, ARG_INCOME)?)?, threshold))
+}
+
+/// Print the outcome of each shock
+///
+/// # Arguments
+/// * `results` - the stress results, the first one being the result without shock
+/// * `threshold` - the maximum debt ratio
+pub fn print_stress_results(results: &[StressResult], threshold: f64) {
+    println!(
+        "*** Rate shocks, the debt ratio threshold is {}% ***\n",
+        threshold * 100_f64
+    );
+    let mut stress_table = table!([
+        "Shock",
+        "Worst term price",
+        "Total interest",
+        "Interest change",
+        "Debt ratio",
+        "Threshold breached",
+    ]);
+    for result in results {
+        stress_table.add_row(row![
+            result.shock,
+            format!("{:.2}", result.worst_term_price),
+            format!("{:.2}", result.total_interest),
+            format!("{:+.2}", result.interest_change),
+            format!("{:.2}%", result.debt_ratio * 100_f64),
+            if result.breached { "YES" } else { "no" },
+        ]);
+    }
+    stress_table.printstd();
+}
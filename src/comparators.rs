@@ -1,10 +1,18 @@
+use contribution::ContributionPlan;
+use error::{check_rate, HomeCalcError};
+use inflation::Inflation;
 use investment::Investment;
 use loan::Loan;
+use owner_costs::{CostBase, OwnerCost};
+use portfolio::Portfolio;
+use rent::RentPlan;
+use tax::Wrapper;
 
 /// The default periodicity for an home purchase
 pub const PERIODICITY: u8 = 12;
 
 /// A home and invest comparator
+#[derive(Debug, Clone)]
 pub struct HomeInvest {
     pub supply: u32,
     pub loan: u32,
@@ -15,9 +23,22 @@ pub struct HomeInvest {
     pub rent: u32,
     pub invest_rate: f32,
     pub years: u8,
+    pub wrapper: Option<Wrapper>,
+    pub portfolio: Option<Portfolio>,
+    /// Other loans financing the purchase with the main one, e.g. the loan of a PEL
+    pub other_loans: Vec<Loan>,
+    /// How the rent evolves from its initial amount
+    pub rent_plan: RentPlan,
+    /// Yearly variations of the home price replacing the appreciation, e.g. a regional index
+    pub price_path: Option<Inflation>,
+    /// Area of the home in square meters
+    pub area: Option<u32>,
+    /// Costs of the owner detailed on top of the annual charges
+    pub owner_costs: Vec<OwnerCost>,
 }
 
 impl HomeInvest {
+    /// Returns a comparator given all its parameters, or an error for an invalid parameter
     pub fn new(
         supply: u32,
         loan: u32,
@@ -28,8 +49,18 @@ impl HomeInvest {
         rent: u32,
         invest_rate: f32,
         years: u8,
-    ) -> HomeInvest {
-        HomeInvest {
+    ) -> Result<HomeInvest, HomeCalcError> {
+        check_rate("purchase charges", purchase_charges)?;
+        check_rate("annual charges", annual_charges)?;
+        if !annual_appreciation_rate.is_finite() || annual_appreciation_rate <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "home appreciation",
+                value: annual_appreciation_rate,
+            });
+        }
+        check_rate("investment rate", invest_rate)?;
+        Loan::new(years, PERIODICITY, loan_rate, loan)?;
+        Ok(HomeInvest {
             supply,
             loan,
             loan_rate,
@@ -39,36 +70,291 @@ impl HomeInvest {
             rent,
             invest_rate,
             years,
+            wrapper: None,
+            portfolio: None,
+            other_loans: Vec::new(),
+            rent_plan: RentPlan::default(),
+            price_path: None,
+            area: None,
+            owner_costs: Vec::new(),
+        })
+    }
+
+    /// Returns the comparator with the difference invested in an account taxed on withdrawal
+    ///
+    /// The capital of the investment is then compared after tax, the gain on the sale of the
+    /// main residence being tax free.
+    ///
+    /// # Arguments
+    /// * `wrapper` - the account holding the investment
+    pub fn with_wrapper(mut self, wrapper: Wrapper) -> HomeInvest {
+        self.wrapper = Some(wrapper);
+        self
+    }
+
+    /// Returns the comparator with the difference invested in a portfolio of asset classes
+    ///
+    /// The investment rate is then replaced by the yields of the assets.
+    ///
+    /// # Arguments
+    /// * `portfolio` - the portfolio
+    pub fn with_portfolio(mut self, portfolio: Portfolio) -> HomeInvest {
+        self.portfolio = Some(portfolio);
+        self
+    }
+
+    /// Returns the comparator with another loan financing the purchase on top of the main one
+    ///
+    /// The loan is repaid monthly, its terms being invested when renting until it ends.
+    ///
+    /// # Arguments
+    /// * `loan` - the loan, e.g. the loan granted by a home savings plan
+    pub fn with_loan(mut self, loan: Loan) -> Result<HomeInvest, HomeCalcError> {
+        if loan.period != PERIODICITY {
+            return Err(HomeCalcError::InvalidPeriodicity(loan.period));
         }
+        self.other_loans.push(loan);
+        Ok(self)
+    }
+
+    /// Returns the comparator with a rent revised every year or changing when moving
+    ///
+    /// The difference invested each month follows the rent.
+    ///
+    /// # Arguments
+    /// * `rent_plan` - how the rent evolves
+    pub fn with_rent_plan(mut self, rent_plan: RentPlan) -> HomeInvest {
+        self.rent_plan = rent_plan;
+        self
+    }
+
+    /// Returns the comparator with the home price following a path rather than the appreciation
+    ///
+    /// # Arguments
+    /// * `price_path` - the variation of the home price of each year
+    pub fn with_price_path(mut self, price_path: Inflation) -> HomeInvest {
+        self.price_path = Some(price_path);
+        self
+    }
+
+    /// Returns the comparator with the area of the home, needed by the costs by square meter
+    ///
+    /// # Arguments
+    /// * `area` - the area in square meters
+    pub fn with_area(mut self, area: u32) -> HomeInvest {
+        self.area = Some(area);
+        self
+    }
+
+    /// Returns the comparator with a cost of the owner
+    ///
+    /// A cost by square meter needs the area of the home, the computations return an error
+    /// otherwise.
+    ///
+    /// # Arguments
+    /// * `cost` - the cost, e.g. the property tax
+    pub fn with_owner_cost(mut self, cost: OwnerCost) -> HomeInvest {
+        self.owner_costs.push(cost);
+        self
+    }
+
+    /// Check that the home has an area when a cost of the owner is by square meter
+    fn check_area(&self) -> Result<(), HomeCalcError> {
+        let by_area = self
+            .owner_costs
+            .iter()
+            .any(|cost| matches!(cost.base, CostBase::PerSquareMeter(_)));
+        if by_area && self.area.is_none() {
+            return Err(HomeCalcError::MissingArea);
+        }
+        Ok(())
+    }
+
+    /// Return the amount of a cost of the owner paid at a period
+    ///
+    /// # Arguments
+    /// * `cost` - the cost
+    /// * `period` - the period
+    pub fn owner_cost_at(&self, cost: &OwnerCost, period: u32) -> f64 {
+        let area = self.area.unwrap_or(0);
+        cost.amount_at(self.home_value(), area, PERIODICITY, period)
+    }
+
+    /// Return the total of a cost of the owner paid up to a period
+    ///
+    /// # Arguments
+    /// * `cost` - the cost
+    /// * `period` - the last period
+    pub fn owner_cost_total(&self, cost: &OwnerCost, period: u32) -> f64 {
+        (1..=period).map(|at| self.owner_cost_at(cost, at)).sum()
+    }
+
+    /// Return the annual charges paid at a period, the detailed costs of the owner included
+    fn charges_at(&self, period: u32) -> f64 {
+        self.annual_charges as f64 / PERIODICITY as f64 * self.home_value()
+            + self
+                .owner_costs
+                .iter()
+                .map(|cost| self.owner_cost_at(cost, period))
+                .sum::<f64>()
+    }
+
+    /// Return the rent of a period
+    pub fn rent_at(&self, period: u32) -> f64 {
+        self.rent_plan.rent_at(self.rent, PERIODICITY, period)
+    }
+
+    fn home_loan(&self) -> Result<Loan, HomeCalcError> {
+        Loan::new(self.years, PERIODICITY, self.loan_rate, self.loan)
+    }
+
+    /// Return the capital borrowed for the purchase
+    fn borrowed(&self) -> f64 {
+        self.loan as f64 + self.other_loans.iter().map(|loan| loan.capital as f64).sum::<f64>()
+    }
+
+    /// Return the sum of a value of the other loans, up to their end
+    fn other_loans_total<F>(&self, period: u32, value: F) -> Result<f64, HomeCalcError>
+    where
+        F: Fn(&Loan, u32) -> Result<f64, HomeCalcError>,
+    {
+        self.other_loans
+            .iter()
+            .map(|loan| value(loan, period.min(loan.periods())))
+            .sum()
+    }
+
+    /// Return the total term price of the other loans at a period
+    ///
+    /// # Arguments
+    /// * `period` - the period, starting at 1
+    pub fn other_terms_at(&self, period: u32) -> f64 {
+        self.other_loans
+            .iter()
+            .filter(|loan| period <= loan.periods())
+            .map(|loan| loan.term_price())
+            .sum()
+    }
+
+    /// Return the value of the home at the purchase, the purchase charges excluded
+    fn home_value(&self) -> f64 {
+        (self.supply as f64 + self.borrowed()) / (1_f64 + self.purchase_charges as f64)
+    }
+
+    /// Return the value of the home at a period, the appreciation being compounded
+    ///
+    /// # Arguments
+    /// * `period` - the period
+    pub fn home_value_at(&self, period: u32) -> f64 {
+        let level = match self.price_path {
+            Some(ref price_path) => price_path.deflator(period, PERIODICITY),
+            None => (1_f64 + self.annual_appreciation_rate as f64)
+                .powf(period as f64 / PERIODICITY as f64),
+        };
+        self.home_value() * level
+    }
+
+    /// Return the cash flows of buying rather than renting, the home being sold at a period
+    ///
+    /// The supply is paid at the purchase, then each month the loan term and the annual charges
+    /// are paid while the rent is saved. The home value net of the loan balance comes back at
+    /// the last period.
+    ///
+    /// # Arguments
+    /// * `period` - the period of the sale
+    ///
+    /// # Example
+    /// ```
+    /// // The return of the purchase by month when the home is sold after 10 years
+    /// let rate = cash_flow::irr(&home_invest.cash_flows(120)?)?;
+    /// ```
+    pub fn cash_flows(&self, period: u32) -> Result<Vec<f64>, HomeCalcError> {
+        self.check_area()?;
+        let loan = self.home_loan()?;
+        let mut flows: Vec<f64> = (0..=period)
+            .map(|at| {
+                self.rent_at(at) - loan.term_price() - self.other_terms_at(at) - self.charges_at(at)
+            }).collect();
+        flows[0] = -(self.supply as f64);
+        flows[period as usize] += self.home_value_at(period) - loan.balance_at(period)?
+            - self.other_loans_total(period, Loan::balance_at)?;
+        Ok(flows)
+    }
+
+    /// Return the investment of a monthly difference of costs, which follows its changes
+    ///
+    /// # Arguments
+    /// * `capital` - the capital invested at the start
+    /// * `difference` - the monthly difference of a period, nothing is invested when negative
+    /// * `period` - the last period of the investment
+    fn difference_investment<F>(
+        &self,
+        capital: u32,
+        difference: F,
+        period: u32,
+    ) -> Result<Investment, HomeCalcError>
+    where
+        F: Fn(u32) -> f64,
+    {
+        // The contributions of an investment are whole amounts: the difference is rounded to the
+        // nearest unit so that the errors don't all go the same way
+        let addition_at = |at| difference(at).max(0_f64).round() as u32;
+        let addition = addition_at(1);
+        let mut plan = ContributionPlan::default();
+        let mut previous = addition;
+        for at in 2..=period {
+            let current = addition_at(at);
+            if current != previous {
+                plan = plan.with_change(at, current);
+                previous = current;
+            }
+        }
+        let mut invest =
+            Investment::new(capital, PERIODICITY, self.invest_rate, addition)?.with_plan(plan);
+        if let Some(wrapper) = self.wrapper {
+            invest = invest.with_wrapper(wrapper);
+        }
+        if let Some(ref portfolio) = self.portfolio {
+            invest = invest.with_portfolio(portfolio.clone());
+        }
+        Ok(invest)
     }
 
     /// Return the capital at some point for a real estate purchase and a financial investment
     ///
+    /// The side with the lower monthly cost invests the difference: the renter while the loan
+    /// terms and the charges of the owner exceed the rent, the owner once the rent exceeds them.
+    /// The capital of the owner includes its investment.
+    ///
     /// # Arguments
     /// * `period` - the comparation point
-    pub fn capital_at(&self, period: u32) -> (f64, f64) {
-        let loan = Loan::new(self.years, PERIODICITY, self.loan_rate, self.loan);
-        let invest = Investment::new(
-            self.supply,
-            PERIODICITY,
-            self.invest_rate,
-            loan.term_price() as u32 - self.rent,
-        );
-        let total_paid = self.supply + self.loan;
-        let home_value = total_paid as f64 / (1_f64 + self.purchase_charges as f64);
-        let loan_capital = self.supply as f64 + loan.capital_at(period)
-            - (total_paid as f64 - home_value)
-            - (self.annual_charges as f64 / PERIODICITY as f64) * home_value * period as f64
-            + home_value
-                * (self.annual_appreciation_rate as f64 / PERIODICITY as f64)
-                * period as f64;
-        return (loan_capital, invest.capital_at(period));
-    }
-
-    /// Returns the loan term price for the home purchase
-    pub fn loan_term_price(&self) -> f64 {
-        let loan = Loan::new(self.years, PERIODICITY, self.loan_rate, self.loan);
-        loan.term_price()
+    pub fn capital_at(&self, period: u32) -> Result<(f64, f64), HomeCalcError> {
+        self.check_area()?;
+        let loan = self.home_loan()?;
+        // The difference follows the rent and the charges, and drops when another loan ends
+        let difference = |at| {
+            loan.term_price() + self.other_terms_at(at) + self.charges_at(at) - self.rent_at(at)
+        };
+        let renter = self.difference_investment(self.supply, difference, period)?;
+        let owner = self.difference_investment(0, |at| -difference(at), period)?;
+        let total_paid = self.supply as f64 + self.borrowed();
+        let home_value = self.home_value();
+        let loan_capital = self.supply as f64
+            + loan.capital_at(period)?
+            + self.other_loans_total(period, Loan::capital_at)?
+            - (total_paid - home_value)
+            - (1..=period).map(|at| self.charges_at(at)).sum::<f64>()
+            + self.home_value_at(period)
+            - home_value;
+        Ok((
+            loan_capital + owner.after_tax_capital_at(period),
+            renter.after_tax_capital_at(period),
+        ))
+    }
+
+    /// Returns the loan term price for the home purchase, the other loans included
+    pub fn loan_term_price(&self) -> Result<f64, HomeCalcError> {
+        Ok(self.home_loan()?.term_price() + self.other_terms_at(1))
     }
 }
 
@@ -78,14 +364,21 @@ mod tests {
 
     use self::float_cmp::ApproxEq;
     use super::*;
+    use cash_flow::{irr, npv};
+    use home_price::Crash;
+    use owner_costs::CostKind;
+    use portfolio::{Asset, Rebalancing};
+
+    fn home_invest() -> HomeInvest {
+        HomeInvest::new(43063, 344500, 0.018, 0.125, 0.02, 0.025, 1050, 0.04, 25).unwrap()
+    }
 
     #[test]
     fn test_home_invest() {
-        let home_comparator =
-            HomeInvest::new(43063, 344500, 0.018, 0.125, 0.02, 0.025, 1050, 0.04, 25);
-        let expected_capital_loan = 136788.22;
-        let expected_capital_invest = 119565.65;
-        let (capital_loan, capital_invest) = home_comparator.capital_at(120);
+        let home_comparator = home_invest();
+        let expected_capital_loan = 147152.37;
+        let expected_capital_invest = 204234.29;
+        let (capital_loan, capital_invest) = home_comparator.capital_at(120).unwrap();
         assert!(
             &expected_capital_loan.approx_eq(
                 &capital_loan,
@@ -108,4 +401,213 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_after_tax_home_invest() {
+        let home_invest = home_invest();
+        let (_, gross) = home_invest.capital_at(120).unwrap();
+        let (_, livret_a) = home_invest
+            .clone()
+            .with_wrapper(Wrapper::LivretA)
+            .capital_at(120)
+            .unwrap();
+        let (_, taxable) = home_invest
+            .with_wrapper(Wrapper::Taxable)
+            .capital_at(120)
+            .unwrap();
+        assert_eq!(gross, livret_a);
+        assert!(taxable < gross);
+    }
+
+    #[test]
+    fn test_cash_flows() {
+        let home_invest =
+            HomeInvest::new(45_000, 180_000, 0.02, 0.125, 0.012, 0.02, 900, 0.04, 20).unwrap();
+        let flows = home_invest.cash_flows(120).unwrap();
+        assert_eq!(121, flows.len());
+        assert_eq!(-45_000_f64, flows[0]);
+        let term_price = home_invest.loan_term_price().unwrap();
+        assert!((flows[1] - (900_f64 - term_price - 200_f64)).abs() < 1e-3);
+        let rate = irr(&flows).unwrap();
+        assert!(npv(rate, &flows).abs() < 1e-2);
+        assert!(home_invest.cash_flows(300).is_err());
+    }
+
+    #[test]
+    fn test_portfolio_home_invest() {
+        let home_invest = home_invest();
+        let (_, single) = home_invest.capital_at(120).unwrap();
+        let portfolio = |bonds_yield| {
+            Portfolio::new(
+                vec![
+                    Asset::new("stocks", 0.04, 0.15, 0.5).unwrap(),
+                    Asset::new("bonds", bonds_yield, 0.05, 0.5).unwrap(),
+                ],
+                Rebalancing::Periodic(12),
+            ).unwrap()
+        };
+        let (_, same) = home_invest
+            .clone()
+            .with_portfolio(portfolio(0.04))
+            .capital_at(120)
+            .unwrap();
+        let (_, lower) = home_invest
+            .with_portfolio(portfolio(0.02))
+            .capital_at(120)
+            .unwrap();
+        assert!((single - same).abs() / single < 1e-6);
+        assert!(lower < single);
+    }
+
+    #[test]
+    fn test_other_loans() {
+        let single = home_invest();
+        let split = HomeInvest {
+            loan: 300000,
+            ..home_invest()
+        }.with_loan(Loan::new(25, 12, 0.018, 44500).unwrap())
+            .unwrap();
+        let (single_loan, single_invest) = single.capital_at(120).unwrap();
+        let (split_loan, split_invest) = split.capital_at(120).unwrap();
+        assert!((single_loan - split_loan).abs() < 1_f64);
+        assert!((single_invest - split_invest).abs() / single_invest < 1e-3);
+
+        // The terms of a shorter loan stop, and so does the difference invested
+        let other = Loan::new(10, 12, 0.01, 44500).unwrap();
+        let shorter = HomeInvest {
+            loan: 300000,
+            ..home_invest()
+        }.with_loan(other.clone())
+            .unwrap();
+        let flows = shorter.cash_flows(200).unwrap();
+        assert!((flows[121] - flows[120] - other.term_price()).abs() < 1e-9);
+        let main_term = Loan::new(25, 12, 0.018, 300000).unwrap().term_price();
+        let charges = shorter.charges_at(1);
+        let after = (main_term + charges - 1050_f64).round() as u32;
+        let plan = ContributionPlan::default().with_change(121, after);
+        let addition = (shorter.loan_term_price().unwrap() + charges - 1050_f64).round() as u32;
+        let invest = Investment::new(43063, 12, 0.04, addition)
+            .unwrap()
+            .with_plan(plan);
+        let (_, invest_after) = shorter.capital_at(132).unwrap();
+        assert_eq!(invest.after_tax_capital_at(132), invest_after);
+        assert!(single.clone().with_loan(Loan::new(10, 4, 0.01, 1000).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rent_plan() {
+        let constant = home_invest();
+        let indexed = constant.clone().with_rent_plan(
+            RentPlan::default().with_indexation(Inflation::constant(0.02).unwrap()),
+        );
+        let (constant_loan, constant_invest) = constant.capital_at(120).unwrap();
+        let (indexed_loan, indexed_invest) = indexed.capital_at(120).unwrap();
+        assert_eq!(constant_loan, indexed_loan);
+        assert!(indexed_invest < constant_invest);
+        let flows = indexed.cash_flows(120).unwrap();
+        assert!((flows[13] - flows[12] - 21_f64).abs() < 1e-3);
+
+        // Moving to a flat as expensive as the terms and the charges stops the investment
+        let owner_cost = constant.loan_term_price().unwrap() + constant.charges_at(1);
+        let moving = constant
+            .clone()
+            .with_rent_plan(RentPlan::default().with_change(61, owner_cost.ceil() as u32));
+        let (_, moving_invest) = moving.capital_at(120).unwrap();
+        let addition = (owner_cost - 1050_f64).round() as u32;
+        let plan = ContributionPlan::default().with_change(61, 0);
+        let invest = Investment::new(43063, 12, 0.04, addition)
+            .unwrap()
+            .with_plan(plan);
+        assert_eq!(invest.after_tax_capital_at(120), moving_invest);
+    }
+
+    #[test]
+    fn test_price_path() {
+        let home_invest = home_invest();
+        let home_value = home_invest.home_value();
+        assert!((home_invest.home_value_at(120) - home_value * 1.025_f64.powi(10)).abs() < 1e-2);
+        let same = home_invest
+            .clone()
+            .with_price_path(Inflation::constant(0.025).unwrap());
+        assert!((same.home_value_at(120) - home_invest.home_value_at(120)).abs() < 1e-2);
+
+        let crash = Crash::new(2, -0.2, 3).unwrap().price_path(0.025).unwrap();
+        let crashed = home_invest.clone().with_price_path(crash);
+        let (loan, _) = home_invest.capital_at(24).unwrap();
+        let (crashed_loan, _) = crashed.capital_at(24).unwrap();
+        let lost = home_value * 1.025 * (1.025 - 0.8);
+        assert!((loan - crashed_loan - lost).abs() < 1e-2);
+        let (loan, _) = home_invest.capital_at(60).unwrap();
+        let (crashed_loan, _) = crashed.capital_at(60).unwrap();
+        assert!((loan - crashed_loan).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_owner_invests() {
+        let cheap_rent = home_invest();
+        let expensive_rent = HomeInvest {
+            rent: 2500,
+            ..home_invest()
+        };
+        let (home, _) = cheap_rent.capital_at(120).unwrap();
+        let (owner, renter) = expensive_rent.capital_at(120).unwrap();
+        // The renter only keeps the supply invested, the owner invests the rent saved
+        let supply = Investment::new(43063, 12, 0.04, 0).unwrap();
+        assert_eq!(supply.after_tax_capital_at(120), renter);
+        let owner_cost = expensive_rent.loan_term_price().unwrap() + expensive_rent.charges_at(1);
+        let surplus = (2500_f64 - owner_cost).round() as u32;
+        let saved = Investment::new(0, 12, 0.04, surplus).unwrap();
+        assert!((owner - home - saved.after_tax_capital_at(120)).abs() < 1e-6);
+
+        // Once the rent is revised above the terms, the owner invests instead of the renter
+        let indexed = cheap_rent.with_rent_plan(
+            RentPlan::default().with_indexation(Inflation::constant(0.1).unwrap()),
+        );
+        let (owner, renter) = indexed.capital_at(120).unwrap();
+        assert!(owner > home);
+        assert!(renter > supply.after_tax_capital_at(120));
+    }
+
+    #[test]
+    fn test_owner_costs() {
+        let home_invest = home_invest();
+        let tax = OwnerCost::new(CostKind::PropertyTax, CostBase::Fixed(1200), 0.5).unwrap();
+        let co_ownership: OwnerCost = "co-ownership:24:m2".parse().unwrap();
+        let no_area = home_invest.clone().with_owner_cost(co_ownership);
+        match no_area.capital_at(24) {
+            Err(HomeCalcError::MissingArea) => (),
+            _ => panic!("a cost by square meter needs the area"),
+        }
+        assert!(no_area.cash_flows(24).is_err());
+        // The area may be set after the costs
+        let detailed = home_invest
+            .clone()
+            .with_owner_cost(tax)
+            .with_owner_cost(co_ownership)
+            .with_area(50);
+        assert_eq!(1200_f64 + 1800_f64, detailed.owner_cost_total(&tax, 24));
+        assert_eq!(1200_f64 * 2_f64, detailed.owner_cost_total(&co_ownership, 24));
+        let (home, _) = home_invest.capital_at(24).unwrap();
+        let (detailed_home, detailed_invest) = detailed.capital_at(24).unwrap();
+        assert!((home - detailed_home - 5400_f64).abs() < 1e-6);
+        // The renter invests the costs of the owner too
+        let owner_cost = home_invest.loan_term_price().unwrap() + home_invest.charges_at(1);
+        let addition = |costs: f64| (owner_cost + costs - 1050_f64).round() as u32;
+        let plan = ContributionPlan::default().with_change(13, addition(250_f64));
+        let invest = Investment::new(43063, 12, 0.04, addition(200_f64))
+            .unwrap()
+            .with_plan(plan);
+        assert_eq!(invest.after_tax_capital_at(24), detailed_invest);
+        let flows = home_invest.cash_flows(24).unwrap();
+        let detailed_flows = detailed.cash_flows(24).unwrap();
+        assert!((flows[13] - detailed_flows[13] - 250_f64).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_invalid_home_invest() {
+        let negative_rate =
+            HomeInvest::new(43063, 344500, -0.018, 0.125, 0.02, 0.025, 1050, 0.04, 25);
+        assert!(negative_rate.is_err());
+        let no_years = HomeInvest::new(43063, 344500, 0.018, 0.125, 0.02, 0.025, 1050, 0.04, 0);
+        assert!(no_years.is_err());
+    }
 }
@@ -0,0 +1,93 @@
+use error::{check_duration, HomeCalcError};
+use std::fmt;
+use std::str::FromStr;
+
+/// How often the interest of an investment is added to its capital
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Compounding {
+    /// A number of times by year, e.g. once for a Livret A or 365 for a daily compounding
+    Periodic(u32),
+    /// Continuously
+    Continuous,
+}
+
+impl Compounding {
+    /// Returns a compounding a number of times by year, or an error for zero
+    ///
+    /// # Arguments
+    /// * `times` - number of compoundings by year
+    pub fn periodic(times: u32) -> Result<Compounding, HomeCalcError> {
+        check_duration("compounding by year", times)?;
+        Ok(Compounding::Periodic(times))
+    }
+
+    /// Return the rate by period equivalent to a yearly rate compounded this way
+    ///
+    /// # Arguments
+    /// * `yield_rate` - the nominal yield rate by year
+    /// * `periodicity` - number of periods by year
+    ///
+    /// # Example
+    /// ```
+    /// // 3% compounded once a year is 0.2466% by month
+    /// let monthly = Compounding::Periodic(1).period_rate(0.03, 12);
+    /// ```
+    pub fn period_rate(&self, yield_rate: f32, periodicity: u8) -> f64 {
+        let yield_rate = yield_rate as f64;
+        let periodicity = periodicity as f64;
+        match *self {
+            Compounding::Periodic(times) => {
+                let times = times as f64;
+                (1_f64 + yield_rate / times).powf(times / periodicity) - 1_f64
+            }
+            Compounding::Continuous => (yield_rate / periodicity).exp() - 1_f64,
+        }
+    }
+}
+
+impl fmt::Display for Compounding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Compounding::Periodic(1) => write!(f, "compounded yearly"),
+            Compounding::Periodic(times) => write!(f, "compounded {} times by year", times),
+            Compounding::Continuous => write!(f, "compounded continuously"),
+        }
+    }
+}
+
+impl FromStr for Compounding {
+    type Err = HomeCalcError;
+
+    /// Parse a compounding from `continuous` or a number of times by year
+    fn from_str(value: &str) -> Result<Compounding, HomeCalcError> {
+        match value {
+            "continuous" => Ok(Compounding::Continuous),
+            _ => value
+                .parse()
+                .map_err(|_| HomeCalcError::InvalidValue {
+                    name: "compounding".to_string(),
+                    value: value.to_string(),
+                }).and_then(Compounding::periodic),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_rate() {
+        let yearly = Compounding::Periodic(1).period_rate(0.03, 12);
+        assert!(((1_f64 + yearly).powi(12) - 1.03).abs() < 1e-6);
+        let monthly = Compounding::Periodic(12).period_rate(0.125, 12);
+        assert!((monthly - 0.125 / 12_f64).abs() < 1e-12);
+        let daily = Compounding::Periodic(365).period_rate(0.05, 1);
+        assert!((daily - 0.051267).abs() < 1e-6);
+        let continuous = Compounding::Continuous.period_rate(0.05, 1);
+        assert!((continuous - 0.051271).abs() < 1e-6);
+        assert_eq!(Compounding::Continuous, "continuous".parse().unwrap());
+        assert_eq!(Compounding::Periodic(4), "4".parse().unwrap());
+        assert!("0".parse::<Compounding>().is_err());
+    }
+}
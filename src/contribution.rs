@@ -0,0 +1,166 @@
+use error::HomeCalcError;
+use std::str::FromStr;
+
+/// A plan describing how the regular contribution to an investment evolves over time
+///
+/// Periods start at 1, the contribution of a period is added at its end unless the investment
+/// contributes at the start of the periods.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContributionPlan {
+    /// Yearly increase of the regular contribution (e.g. salary growth)
+    pub step_up: f32,
+    /// New regular contributions `(from period, amount)`
+    pub changes: Vec<(u32, u32)>,
+    /// Periods without regular contribution `(from period, to period)`, bounds included
+    pub pauses: Vec<(u32, u32)>,
+    /// One-off contributions `(period, amount)`
+    pub lump_sums: Vec<(u32, u32)>,
+}
+
+impl ContributionPlan {
+    /// Returns a plan with a yearly step-up of the contribution, or an error for an invalid rate
+    ///
+    /// # Arguments
+    /// * `step_up` - yearly increase of the regular contribution
+    ///
+    /// # Example
+    /// ```
+    /// // 3% more each year, nothing during the second year and 5000 at the end of the third
+    /// let plan = ContributionPlan::new(0.03)?
+    ///     .with_pause(13, 24)
+    ///     .with_lump_sum(36, 5000);
+    /// ```
+    pub fn new(step_up: f32) -> Result<ContributionPlan, HomeCalcError> {
+        if !step_up.is_finite() || step_up <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "contribution step-up",
+                value: step_up,
+            });
+        }
+        Ok(ContributionPlan {
+            step_up,
+            ..Default::default()
+        })
+    }
+
+    /// Set a new regular contribution from a period, the step-up restarts from there
+    ///
+    /// # Arguments
+    /// * `from` - the first period of the new contribution
+    /// * `amount` - the new contribution by period
+    pub fn with_change(mut self, from: u32, amount: u32) -> ContributionPlan {
+        self.changes.push((from, amount));
+        self
+    }
+
+    /// Stop the regular contribution between two periods
+    ///
+    /// # Arguments
+    /// * `from` - the first period without contribution
+    /// * `to` - the last period without contribution
+    pub fn with_pause(mut self, from: u32, to: u32) -> ContributionPlan {
+        self.pauses.push((from, to));
+        self
+    }
+
+    /// Add a one-off contribution at a period
+    ///
+    /// # Arguments
+    /// * `period` - the period of the contribution
+    /// * `amount` - the contribution
+    pub fn with_lump_sum(mut self, period: u32, amount: u32) -> ContributionPlan {
+        self.lump_sums.push((period, amount));
+        self
+    }
+
+    /// Return true if the plan keeps the initial regular contribution forever
+    pub fn is_constant(&self) -> bool {
+        self.step_up == 0_f32
+            && self.changes.is_empty()
+            && self.pauses.is_empty()
+            && self.lump_sums.is_empty()
+    }
+
+    /// Return the contribution of a period
+    ///
+    /// # Arguments
+    /// * `regular_addition` - the regular contribution by period at the start of the plan
+    /// * `periodicity` - number of periods by year
+    /// * `period` - the period, starting at 1
+    pub fn contribution_at(&self, regular_addition: u32, periodicity: u8, period: u32) -> f64 {
+        if period == 0 {
+            return 0_f64;
+        }
+        let lump_sums: u32 = self
+            .lump_sums
+            .iter()
+            .filter(|&&(at, _)| at == period)
+            .map(|&(_, amount)| amount)
+            .sum();
+        if self
+            .pauses
+            .iter()
+            .any(|&(from, to)| from <= period && period <= to)
+        {
+            return lump_sums as f64;
+        }
+        let (start, amount) = self
+            .changes
+            .iter()
+            .filter(|&&(from, _)| from <= period)
+            .max_by_key(|&&(from, _)| from)
+            .cloned()
+            .unwrap_or((1, regular_addition));
+        let years = period.saturating_sub(start) / periodicity as u32;
+        amount as f64 * (1_f64 + self.step_up as f64).powi(years as i32) + lump_sums as f64
+    }
+}
+
+/// When the contributions are added within their period
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timing {
+    /// At the end of the period, the contribution earns nothing that period (ordinary annuity)
+    End,
+    /// At the start of the period, the contribution earns the return of the period (annuity due)
+    Start,
+}
+
+impl FromStr for Timing {
+    type Err = HomeCalcError;
+
+    fn from_str(value: &str) -> Result<Timing, HomeCalcError> {
+        match value {
+            "end" => Ok(Timing::End),
+            "start" => Ok(Timing::Start),
+            _ => Err(HomeCalcError::InvalidValue {
+                name: "contribution timing".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contribution_at() {
+        let plan = ContributionPlan::new(0.1)
+            .unwrap()
+            .with_change(25, 200)
+            .with_pause(13, 18)
+            .with_lump_sum(15, 1000);
+        assert!(!plan.is_constant());
+        assert_eq!(0_f64, plan.contribution_at(100, 12, 0));
+        assert_eq!(100_f64, plan.contribution_at(100, 12, 1));
+        assert_eq!(100_f64, plan.contribution_at(100, 12, 12));
+        assert_eq!(0_f64, plan.contribution_at(100, 12, 13));
+        assert_eq!(1000_f64, plan.contribution_at(100, 12, 15));
+        assert!((plan.contribution_at(100, 12, 19) - 110_f64).abs() < 1e-4);
+        assert_eq!(200_f64, plan.contribution_at(100, 12, 36));
+        assert!((plan.contribution_at(100, 12, 37) - 220_f64).abs() < 1e-4);
+        assert!(ContributionPlan::default().is_constant());
+        assert!(ContributionPlan::new(-1_f32).is_err());
+    }
+}
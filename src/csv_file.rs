@@ -0,0 +1,104 @@
+use date::Date;
+use std::io;
+
+/// A line of a CSV file split into fields
+pub struct Record {
+    pub line: usize,
+    pub fields: Vec<String>,
+    decimal_comma: bool,
+}
+
+impl Record {
+    /// Return a field as text
+    ///
+    /// # Arguments
+    /// * `index` - index of the field
+    pub fn text(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(|field| field.as_str())
+    }
+
+    /// Return a field as a number, `;` separated files may use a decimal comma
+    ///
+    /// # Arguments
+    /// * `index` - index of the field
+    pub fn number(&self, index: usize) -> Option<f64> {
+        let field = self.text(index)?.replace(' ', "");
+        let field = if self.decimal_comma {
+            field.replace(',', ".")
+        } else {
+            field
+        };
+        field.parse::<f64>().ok()
+    }
+
+    /// Return a field as a date
+    ///
+    /// # Arguments
+    /// * `index` - index of the field
+    pub fn date(&self, index: usize) -> Option<Date> {
+        Date::parse(self.text(index)?)
+    }
+
+    /// Return an error about this record
+    ///
+    /// # Arguments
+    /// * `message` - what is wrong with the record
+    pub fn error(&self, message: &str) -> io::Error {
+        invalid_data(self.line, message)
+    }
+}
+
+/// Split CSV content into records separated by `;` or `,`
+///
+/// Empty lines and lines starting with `#` are skipped, fields are trimmed and unquoted.
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn records(content: &str) -> Vec<Record> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|&(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line_number, line)| {
+            let separator = if line.contains(';') { ';' } else { ',' };
+            Record {
+                line: line_number,
+                fields: line
+                    .split(separator)
+                    .map(|field| field.trim().trim_matches('"').trim().to_string())
+                    .collect(),
+                decimal_comma: separator == ';',
+            }
+        }).collect()
+}
+
+/// Return an error for invalid data in a file
+///
+/// # Arguments
+/// * `line` - line or entry number of the invalid data
+/// * `message` - what is wrong with the data
+pub fn invalid_data(line: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("entry {}: {}", line, message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let content = "# comment\nname;rate\n\n\"bank\"; 1,25\nother,2.5\n";
+        let records = records(content);
+        assert_eq!(3, records.len());
+        assert_eq!(2, records[0].line);
+        assert_eq!(None, records[0].number(1));
+        assert_eq!(Some("bank"), records[1].text(0));
+        assert_eq!(Some(1.25), records[1].number(1));
+        assert_eq!(Some(2.5), records[2].number(1));
+        assert_eq!(None, records[2].number(2));
+    }
+}
@@ -0,0 +1,211 @@
+use error::HomeCalcError;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A calendar date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Returns a date if the day exists in the calendar
+    ///
+    /// # Arguments
+    /// * `year` - the year
+    /// * `month` - the month (1 to 12)
+    /// * `day` - the day of the month (1 to 31)
+    ///
+    /// # Example
+    /// ```
+    /// let first_due = Date::new(2018, 9, 5).unwrap();
+    /// ```
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Parse a date written as `YYYY-MM-DD`, `DD/MM/YYYY` or `YYYYMMDD`
+    ///
+    /// # Arguments
+    /// * `value` - the date to parse
+    pub fn parse(value: &str) -> Option<Date> {
+        let value = value.trim();
+        let parts: Vec<&str> = if value.contains('-') {
+            value.split('-').collect()
+        } else if value.contains('/') {
+            value.split('/').rev().collect()
+        } else if value.len() >= 8 && value.is_char_boundary(8) {
+            vec![&value[0..4], &value[4..6], &value[6..8]]
+        } else {
+            return None;
+        };
+        if parts.len() != 3 {
+            return None;
+        }
+        Date::new(
+            parts[0].parse().ok()?,
+            parts[1].parse().ok()?,
+            parts[2].parse().ok()?,
+        )
+    }
+
+    /// Return the current date in UTC
+    pub fn today() -> Date {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        Date::from_days((seconds / 86_400) as i64)
+    }
+
+    /// Return the number of days since 1970-01-01
+    pub fn to_days(self) -> i64 {
+        let year = if self.month <= 2 {
+            self.year as i64 - 1
+        } else {
+            self.year as i64
+        };
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let year_of_era = year - era * 400;
+        let month = self.month as i64;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + self.day as i64
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    /// Return the date for a number of days since 1970-01-01
+    ///
+    /// # Arguments
+    /// * `days` - number of days since 1970-01-01
+    pub fn from_days(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = (year_of_era + era * 400) as i32 + if month <= 2 { 1 } else { 0 };
+        Date { year, month, day }
+    }
+
+    /// Return the date some days later (or earlier when negative)
+    ///
+    /// # Arguments
+    /// * `days` - number of days to add
+    pub fn add_days(&self, days: i64) -> Date {
+        Date::from_days(self.to_days() + days)
+    }
+
+    /// Return the date some months later, the day is clamped to the end of the month
+    ///
+    /// # Arguments
+    /// * `months` - number of months to add
+    pub fn add_months(&self, months: u32) -> Date {
+        let months_from_zero = self.year as i64 * 12 + (self.month as i64 - 1) + months as i64;
+        let year = (months_from_zero / 12) as i32;
+        let month = (months_from_zero % 12) as u32 + 1;
+        Date {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+        }
+    }
+
+    /// Return the number of days from this date to another one
+    ///
+    /// # Arguments
+    /// * `other` - the end date
+    pub fn days_until(&self, other: &Date) -> i64 {
+        other.to_days() - self.to_days()
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = HomeCalcError;
+
+    fn from_str(value: &str) -> Result<Date, HomeCalcError> {
+        Date::parse(value).ok_or_else(|| HomeCalcError::InvalidValue {
+            name: "date".to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Return the number of days in a month
+///
+/// # Arguments
+/// * `year` - the year
+/// * `month` - the month (1 to 12)
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns a date known to exist, for the tests
+#[cfg(test)]
+pub fn date(year: i32, month: u32, day: u32) -> Date {
+    Date::new(year, month, day).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let expected = Date::new(2018, 9, 5).unwrap();
+        assert_eq!(Some(expected), Date::parse("2018-09-05"));
+        assert_eq!(Some(expected), Date::parse("05/09/2018"));
+        assert_eq!(Some(expected), Date::parse("20180905120000[+1:CET]"));
+        assert_eq!(None, Date::parse("2018-02-30"));
+        assert_eq!(None, Date::parse("date"));
+    }
+
+    #[test]
+    fn test_days() {
+        assert_eq!(0, Date::new(1970, 1, 1).unwrap().to_days());
+        assert_eq!(17779, Date::new(2018, 9, 5).unwrap().to_days());
+        assert_eq!(Date::new(2000, 2, 29).unwrap(), Date::from_days(11016));
+        let date = Date::new(2016, 12, 31).unwrap();
+        assert_eq!(Date::new(2017, 1, 30).unwrap(), date.add_days(30));
+        let new_year = Date::new(2016, 1, 1).unwrap();
+        assert_eq!(366, new_year.days_until(&Date::new(2017, 1, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_add_months() {
+        let date = Date::new(2018, 1, 31).unwrap();
+        assert_eq!(Date::new(2018, 2, 28).unwrap(), date.add_months(1));
+        assert_eq!(Date::new(2020, 2, 29).unwrap(), date.add_months(25));
+        assert_eq!(Date::new(2019, 1, 31).unwrap(), date.add_months(12));
+    }
+}
@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// The errors returned by the computations
+#[derive(Debug)]
+pub enum HomeCalcError {
+    /// A duration must be at least one
+    InvalidDuration { name: &'static str, value: u32 },
+    /// The periodicity must be at least one term by year
+    InvalidPeriodicity(u8),
+    /// A rate is negative or not a number
+    InvalidRate { name: &'static str, value: f32 },
+    /// An amount is negative or not a number
+    InvalidAmount { name: &'static str, value: f64 },
+    /// A period is after the end of the computation
+    PeriodOutOfRange { period: u32, max: u32 },
+    /// A cost of the owner by square meter is set for a home without area
+    MissingArea,
+    /// A value from the command line or a file can't be parsed
+    InvalidValue { name: String, value: String },
+    /// A file can't be read or contains invalid data
+    Io(io::Error),
+    /// No solution was found
+    NoSolution(&'static str),
+}
+
+impl fmt::Display for HomeCalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HomeCalcError::InvalidDuration { name, value } => {
+                write!(f, "invalid {}: {}, it must be at least 1", name, value)
+            }
+            HomeCalcError::InvalidPeriodicity(value) => write!(
+                f,
+                "invalid periodicity: {}, it must be at least 1 term by year",
+                value
+            ),
+            HomeCalcError::InvalidRate { name, value } => {
+                write!(f, "invalid {}: {}%", name, value * 100_f32)
+            }
+            HomeCalcError::InvalidAmount { name, value } => {
+                write!(f, "invalid {}: {}, it must be positive", name, value)
+            }
+            HomeCalcError::PeriodOutOfRange { period, max } => write!(
+                f,
+                "period {} is out of range, the last period is {}",
+                period, max
+            ),
+            HomeCalcError::MissingArea => write!(
+                f,
+                "an owner cost by square meter needs the area of the home (--area)"
+            ),
+            HomeCalcError::InvalidValue {
+                ref name,
+                ref value,
+            } => write!(f, "invalid value for {}: '{}'", name, value),
+            HomeCalcError::Io(ref error) => write!(f, "{}", error),
+            HomeCalcError::NoSolution(name) => write!(f, "no solution found for {}", name),
+        }
+    }
+}
+
+impl Error for HomeCalcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            HomeCalcError::Io(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for HomeCalcError {
+    fn from(error: io::Error) -> HomeCalcError {
+        HomeCalcError::Io(error)
+    }
+}
+
+/// Check that a rate is a positive number
+///
+/// # Arguments
+/// * `name` - name of the rate
+/// * `value` - the rate
+pub fn check_rate(name: &'static str, value: f32) -> Result<f32, HomeCalcError> {
+    if value.is_finite() && value >= 0_f32 {
+        Ok(value)
+    } else {
+        Err(HomeCalcError::InvalidRate { name, value })
+    }
+}
+
+/// Check that an amount is a positive number
+///
+/// # Arguments
+/// * `name` - name of the amount
+/// * `value` - the amount
+pub fn check_amount(name: &'static str, value: f64) -> Result<f64, HomeCalcError> {
+    if value.is_finite() && value >= 0_f64 {
+        Ok(value)
+    } else {
+        Err(HomeCalcError::InvalidAmount { name, value })
+    }
+}
+
+/// Check that a duration is at least one
+///
+/// # Arguments
+/// * `name` - name of the duration
+/// * `value` - the duration
+pub fn check_duration(name: &'static str, value: u32) -> Result<u32, HomeCalcError> {
+    if value >= 1 {
+        Ok(value)
+    } else {
+        Err(HomeCalcError::InvalidDuration { name, value })
+    }
+}
+
+/// Check that a periodicity is at least one term by year
+///
+/// # Arguments
+/// * `value` - the periodicity
+pub fn check_periodicity(value: u8) -> Result<u8, HomeCalcError> {
+    if value >= 1 {
+        Ok(value)
+    } else {
+        Err(HomeCalcError::InvalidPeriodicity(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks() {
+        assert_eq!(0.02, check_rate("rate", 0.02).unwrap());
+        assert!(check_rate("rate", -0.01).is_err());
+        assert!(check_rate("rate", f32::NAN).is_err());
+        assert!(check_amount("capital", -1_f64).is_err());
+        assert!(check_duration("years", 0).is_err());
+        assert!(check_periodicity(0).is_err());
+        assert_eq!(
+            "invalid rate: -1%",
+            check_rate("rate", -0.01).unwrap_err().to_string()
+        );
+    }
+}
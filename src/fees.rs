@@ -0,0 +1,73 @@
+use error::{check_rate, HomeCalcError};
+
+/// The fees charged by a fund
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Fees {
+    /// Ratio of each contribution kept by the fund
+    pub entry: f32,
+    /// Ratio of the assets charged by year
+    pub management: f32,
+}
+
+impl Fees {
+    /// Returns the fees of a fund, or an error for an invalid rate
+    ///
+    /// # Arguments
+    /// * `entry` - ratio of each contribution kept by the fund
+    /// * `management` - ratio of the assets charged by year
+    ///
+    /// # Example
+    /// ```
+    /// // 2% on each contribution and 0.85% of the assets by year
+    /// let fees = Fees::new(0.02, 0.0085)?;
+    /// ```
+    pub fn new(entry: f32, management: f32) -> Result<Fees, HomeCalcError> {
+        check_rate("entry fees", entry)?;
+        check_rate("management fees", management)?;
+        if entry > 1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "entry fees",
+                value: entry,
+            });
+        }
+        Ok(Fees { entry, management })
+    }
+
+    /// Return true if the fund charges nothing
+    pub fn is_free(&self) -> bool {
+        self.entry == 0_f32 && self.management == 0_f32
+    }
+
+    /// Return the entry fees charged on a contribution
+    ///
+    /// # Arguments
+    /// * `contribution` - the contribution
+    pub fn entry_fee(&self, contribution: f64) -> f64 {
+        contribution * self.entry as f64
+    }
+
+    /// Return the management fees charged on a balance for a period
+    ///
+    /// # Arguments
+    /// * `balance` - the balance at the end of the period
+    /// * `periodicity` - number of periods by year
+    pub fn management_fee(&self, balance: f64, periodicity: u8) -> f64 {
+        balance.max(0_f64) * self.management as f64 / periodicity as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fees() {
+        let fees = Fees::new(0.02, 0.012).unwrap();
+        assert!(!fees.is_free());
+        assert!((fees.entry_fee(1000_f64) - 20_f64).abs() < 1e-4);
+        assert!((fees.management_fee(10_000_f64, 12) - 10_f64).abs() < 1e-4);
+        assert!(Fees::default().is_free());
+        assert!(Fees::new(1.5, 0.01).is_err());
+        assert!(Fees::new(0.01, -0.01).is_err());
+    }
+}
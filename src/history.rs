@@ -0,0 +1,154 @@
+use csv_file::records;
+use date::Date;
+use error::{check_duration, HomeCalcError};
+use investment::Investment;
+use std::fs;
+use std::path::Path;
+
+/// A series of historical returns of an index, one by period
+pub struct ReturnSeries {
+    pub values: Vec<(Date, f64)>,
+}
+
+impl ReturnSeries {
+    /// Returns a series given its returns, in any order
+    ///
+    /// # Arguments
+    /// * `values` - dates and returns of the periods
+    pub fn new(mut values: Vec<(Date, f64)>) -> ReturnSeries {
+        values.sort_by_key(|&(date, _)| date);
+        ReturnSeries { values }
+    }
+
+    /// Return the returns in chronological order
+    pub fn returns(&self) -> Vec<f64> {
+        self.values.iter().map(|&(_, value)| value).collect()
+    }
+}
+
+/// The outcomes of an investment started at every date of a historical series
+pub struct Backtest {
+    /// The start date and the final capital of each window, from the worst to the best
+    pub outcomes: Vec<(Date, f64)>,
+}
+
+impl Backtest {
+    /// Return the start date and the final capital of the worst window
+    pub fn worst(&self) -> (Date, f64) {
+        self.outcomes[0]
+    }
+
+    /// Return the start date and the final capital of the median window
+    pub fn median(&self) -> (Date, f64) {
+        self.outcomes[self.outcomes.len() / 2]
+    }
+
+    /// Return the start date and the final capital of the best window
+    pub fn best(&self) -> (Date, f64) {
+        self.outcomes[self.outcomes.len() - 1]
+    }
+}
+
+/// Run an investment over every rolling window of a historical series
+///
+/// The contributions, fees and withdrawals of the investment apply, its yield rate is replaced
+/// by the historical returns, which must have the periodicity of the investment.
+///
+/// # Arguments
+/// * `invest` - the investment
+/// * `series` - the historical returns
+/// * `n_period` - number of periods of a window
+///
+/// # Example
+/// ```
+/// // Every 20 years window of the series
+/// let backtest = backtest(&invest, &load_returns(Path::new("msci_world.csv"))?, 240)?;
+/// let (start, capital) = backtest.worst();
+/// ```
+pub fn backtest(
+    invest: &Investment,
+    series: &ReturnSeries,
+    n_period: u32,
+) -> Result<Backtest, HomeCalcError> {
+    check_duration("window", n_period)?;
+    let returns = series.returns();
+    if n_period as usize > returns.len() {
+        return Err(HomeCalcError::PeriodOutOfRange {
+            period: n_period,
+            max: returns.len() as u32,
+        });
+    }
+    let mut outcomes: Vec<(Date, f64)> = returns
+        .windows(n_period as usize)
+        .zip(series.values.iter())
+        .map(|(window, &(start, _))| (start, invest.capital_path(window)[n_period as usize]))
+        .collect();
+    outcomes.sort_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(Backtest { outcomes })
+}
+
+/// Load historical returns from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_returns(path: &Path) -> Result<ReturnSeries, HomeCalcError> {
+    parse_returns(&fs::read_to_string(path)?)
+}
+
+/// Parse historical returns from CSV lines of `date;return`, the return being in percent
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_returns(content: &str) -> Result<ReturnSeries, HomeCalcError> {
+    let mut values = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let value = record
+            .number(1)
+            .filter(|value| *value > -100_f64)
+            .ok_or_else(|| record.error("invalid return"))?;
+        values.push((date, value / 100_f64));
+    }
+    if values.is_empty() {
+        return Err(HomeCalcError::InvalidValue {
+            name: "returns".to_string(),
+            value: String::new(),
+        });
+    }
+    Ok(ReturnSeries::new(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_returns() {
+        let series = parse_returns("date;return\n2020-02-01;-1,5\n2020-01-01;2\n").unwrap();
+        assert_eq!(vec![0.02, -0.015], series.returns());
+        assert!(parse_returns("2020-01-01;-120").is_err());
+        assert!(parse_returns("date;return\n").is_err());
+    }
+
+    #[test]
+    fn test_backtest() {
+        let series = parse_returns(
+            "date;return\n2020-01-01;10\n2020-02-01;-10\n2020-03-01;0\n2020-04-01;20\n",
+        ).unwrap();
+        let invest = Investment::new(1000, 12, 0.05, 100).unwrap();
+        let result = backtest(&invest, &series, 2).unwrap();
+        assert_eq!(3, result.outcomes.len());
+        let (start, capital) = result.worst();
+        assert_eq!(Date::new(2020, 2, 1).unwrap(), start);
+        assert!((capital - 1100_f64).abs() < 1e-6);
+        let (start, capital) = result.best();
+        assert_eq!(Date::new(2020, 3, 1).unwrap(), start);
+        assert!((capital - 1420_f64).abs() < 1e-6);
+        assert!((result.median().1 - 1180_f64).abs() < 1e-6);
+        assert!(backtest(&invest, &series, 5).is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use error::HomeCalcError;
+use inflation::Inflation;
+use std::fmt;
+use std::str::FromStr;
+
+/// A crash of the home prices followed by a recovery toward their trend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Crash {
+    /// The year of the crash, starting at 1
+    pub year: u32,
+    /// Variation of the price during the crash year, e.g. -0.2
+    pub drop: f32,
+    /// Number of years to get back on the trend, the price staying below it when none
+    pub recovery_years: u32,
+}
+
+impl Crash {
+    /// Returns a crash, or an error for an invalid year or drop
+    ///
+    /// # Arguments
+    /// * `year` - the year of the crash, starting at 1
+    /// * `drop` - variation of the price during the crash year
+    /// * `recovery_years` - number of years to get back on the trend
+    ///
+    /// # Example
+    /// ```
+    /// // -20% during the 3rd year, back on the trend 5 years later
+    /// let crash = Crash::new(3, -0.2, 5)?;
+    /// ```
+    pub fn new(year: u32, drop: f32, recovery_years: u32) -> Result<Crash, HomeCalcError> {
+        if year == 0 {
+            return Err(HomeCalcError::InvalidDuration {
+                name: "crash year",
+                value: year,
+            });
+        }
+        if !drop.is_finite() || drop <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "price drop",
+                value: drop,
+            });
+        }
+        Ok(Crash {
+            year,
+            drop,
+            recovery_years,
+        })
+    }
+
+    /// Return the yearly variations of the home price through the crash
+    ///
+    /// The price follows the appreciation, drops during the crash year then rises evenly until
+    /// it is back on the trend, which it follows afterwards.
+    ///
+    /// # Arguments
+    /// * `appreciation` - appreciation of the home price by year outside of the crash
+    pub fn price_path(&self, appreciation: f32) -> Result<Inflation, HomeCalcError> {
+        let mut rates = vec![appreciation; self.year as usize - 1];
+        rates.push(self.drop);
+        if self.recovery_years > 0 {
+            let years = self.recovery_years as f64;
+            let trend = (1_f64 + appreciation as f64).powf(years + 1_f64);
+            let recovery = (trend / (1_f64 + self.drop as f64)).powf(1_f64 / years) - 1_f64;
+            rates.extend(vec![recovery as f32; self.recovery_years as usize]);
+        }
+        rates.push(appreciation);
+        Inflation::new(rates)
+    }
+}
+
+impl fmt::Display for Crash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}% during year {} then a recovery over {} years",
+            self.drop * 100_f32,
+            self.year,
+            self.recovery_years
+        )
+    }
+}
+
+impl FromStr for Crash {
+    type Err = HomeCalcError;
+
+    /// Parse a crash from `year:drop:recovery-years`, the drop being in percent
+    fn from_str(value: &str) -> Result<Crash, HomeCalcError> {
+        let invalid = || HomeCalcError::InvalidValue {
+            name: "price crash".to_string(),
+            value: value.to_string(),
+        };
+        let fields: Vec<&str> = value.split(':').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let year: u32 = fields[0].parse().map_err(|_| invalid())?;
+        let drop: f32 = fields[1].parse().map_err(|_| invalid())?;
+        let recovery_years: u32 = fields[2].parse().map_err(|_| invalid())?;
+        Crash::new(year, drop / 100_f32, recovery_years)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_path() {
+        let crash: Crash = "3:-20:4".parse().unwrap();
+        let path = crash.price_path(0.02).unwrap();
+        assert_eq!(0.02, path.rate_of_year(1));
+        assert_eq!(-0.2, path.rate_of_year(2));
+        assert_eq!(0.02, path.rate_of_year(7));
+        // Back on the trend at the end of the recovery
+        let trend = 1.02_f64.powi(7);
+        assert!((path.deflator(84, 12) - trend).abs() < 1e-6);
+        assert!(path.deflator(36, 12) < 1_f64);
+
+        let no_recovery = Crash::new(1, -0.1, 0).unwrap().price_path(0.02).unwrap();
+        assert!((no_recovery.deflator(24, 12) - 0.9 * 1.02).abs() < 1e-6);
+        assert!("0:-20:4".parse::<Crash>().is_err());
+        assert!("3:-120:4".parse::<Crash>().is_err());
+        assert!("3:-20".parse::<Crash>().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use comparators::PERIODICITY;
+use error::HomeCalcError;
+use loan::Loan;
+use regulated::{AccountKind, RegulatedAccount, Savings};
+use tax::{FLAT_INCOME_TAX, SOCIAL_CHARGES};
+
+/// Ratio of the interest earned while saving that the interest of the loan can't exceed
+const INTEREST_COEFFICIENT: f64 = 2.5;
+/// Minimum and maximum duration of the loan in years
+const LOAN_YEARS: (u8, u8) = (2, 15);
+
+/// A plan d'épargne logement or a compte d'épargne logement, fed every month to get a loan
+#[derive(Clone)]
+pub struct HomeSavingsPlan {
+    pub account: RegulatedAccount,
+    pub deposit: u32,
+}
+
+/// The rules of a kind of home savings plan
+struct Rules {
+    /// Minimum balance at the opening
+    opening_deposit: u32,
+    /// Minimum monthly deposit, unless nothing is deposited for a CEL
+    deposit: u32,
+    /// Minimum and maximum number of months of saving before the loan
+    months: (u32, u32),
+    /// Rate added to the savings rate to get the loan rate
+    loan_spread: f32,
+    /// Maximum capital of the loan
+    max_loan: u32,
+}
+
+fn rules_of(kind: AccountKind) -> Option<Rules> {
+    match kind {
+        AccountKind::Pel => Some(Rules {
+            opening_deposit: 225,
+            deposit: 45,
+            months: (48, 120),
+            loan_spread: 0.012,
+            max_loan: 92_000,
+        }),
+        AccountKind::Cel => Some(Rules {
+            opening_deposit: 300,
+            deposit: 75,
+            months: (18, 600),
+            loan_spread: 0.015,
+            max_loan: 23_000,
+        }),
+        _ => None,
+    }
+}
+
+fn invalid(name: String, value: u32) -> HomeCalcError {
+    HomeCalcError::InvalidValue {
+        name,
+        value: value.to_string(),
+    }
+}
+
+impl HomeSavingsPlan {
+    /// Returns a plan, or an error when the account isn't a PEL or a CEL or the deposits are
+    /// below the minimums
+    ///
+    /// # Arguments
+    /// * `account` - the PEL or CEL, its balance being the deposit at the opening
+    /// * `deposit` - the amount deposited every month
+    ///
+    /// # Example
+    /// ```
+    /// let pel = RegulatedAccount::new(AccountKind::Pel, 1000, Date::new(2019, 3, 1).unwrap())?;
+    /// let loan = HomeSavingsPlan::new(pel, 200)?.loan(60, 10)?;
+    /// ```
+    pub fn new(account: RegulatedAccount, deposit: u32) -> Result<HomeSavingsPlan, HomeCalcError> {
+        let rules = rules_of(account.kind).ok_or_else(|| HomeCalcError::InvalidValue {
+            name: "home savings plan".to_string(),
+            value: account.kind.to_string(),
+        })?;
+        if account.balance < rules.opening_deposit {
+            return Err(invalid(
+                format!("{} opening deposit (at least {})", account.kind, rules.opening_deposit),
+                account.balance,
+            ));
+        }
+        let no_deposit_allowed = account.kind == AccountKind::Cel && deposit == 0;
+        if deposit < rules.deposit && !no_deposit_allowed {
+            return Err(invalid(
+                format!("{} monthly deposit (at least {})", account.kind, rules.deposit),
+                deposit,
+            ));
+        }
+        Ok(HomeSavingsPlan { account, deposit })
+    }
+
+    fn rules(&self) -> Rules {
+        rules_of(self.account.kind).expect("checked at the creation of the plan")
+    }
+
+    /// Return the savings at the end of the saving phase, or an error for a phase too short
+    /// to get a loan or too long for the deposits of a PEL
+    ///
+    /// # Arguments
+    /// * `n_months` - number of months of saving
+    pub fn savings(&self, n_months: u32) -> Result<Savings, HomeCalcError> {
+        let (min, max) = self.rules().months;
+        if n_months < min || n_months > max {
+            return Err(invalid(
+                format!("{} months of saving ({} to {})", self.account.kind, min, max),
+                n_months,
+            ));
+        }
+        Ok(self.account.savings(self.deposit, n_months))
+    }
+
+    /// Return the tax due on the interest of the saving phase
+    ///
+    /// The interest of the plans opened since 2018 bears the flat tax, the older plans only
+    /// bear the social charges during the saving phase.
+    ///
+    /// # Arguments
+    /// * `n_months` - number of months of saving
+    pub fn tax_on_interest(&self, n_months: u32) -> Result<f64, HomeCalcError> {
+        let interest = self.savings(n_months)?.interest;
+        Ok(if self.account.opening.year >= 2018 {
+            interest * (SOCIAL_CHARGES + FLAT_INCOME_TAX)
+        } else {
+            interest * SOCIAL_CHARGES
+        })
+    }
+
+    /// Return the rate by year of the loan, the savings rate at the request plus a spread
+    ///
+    /// # Arguments
+    /// * `n_months` - number of months of saving
+    pub fn loan_rate(&self, n_months: u32) -> f32 {
+        let request = self.account.opening.add_months(n_months);
+        self.account.rate_at(request) + self.rules().loan_spread
+    }
+
+    /// Return the maximum capital of the loan, whose total interest can't exceed 2.5 times the
+    /// interest earned while saving
+    ///
+    /// # Arguments
+    /// * `n_months` - number of months of saving
+    /// * `years` - duration of the loan in years
+    pub fn max_loan(&self, n_months: u32, years: u8) -> Result<u32, HomeCalcError> {
+        if years < LOAN_YEARS.0 || years > LOAN_YEARS.1 {
+            return Err(invalid(
+                format!(
+                    "years of a {} loan ({} to {})",
+                    self.account.kind, LOAN_YEARS.0, LOAN_YEARS.1
+                ),
+                years as u32,
+            ));
+        }
+        let rights = self.savings(n_months)?.interest * INTEREST_COEFFICIENT;
+        let n_terms = years as u32 * PERIODICITY as u32;
+        let rate_term = self.loan_rate(n_months) as f64 / PERIODICITY as f64;
+        // The interest paid over the whole loan for a capital of 1
+        let unit_interest =
+            rate_term * n_terms as f64 / (1_f64 - (1_f64 + rate_term).powi(-(n_terms as i32)))
+                - 1_f64;
+        Ok(((rights / unit_interest) as u32).min(self.rules().max_loan))
+    }
+
+    /// Return the loan granted at the end of the saving phase for its maximum capital
+    ///
+    /// # Arguments
+    /// * `n_months` - number of months of saving
+    /// * `years` - duration of the loan in years
+    pub fn loan(&self, n_months: u32, years: u8) -> Result<Loan, HomeCalcError> {
+        Loan::new(
+            years,
+            PERIODICITY,
+            self.loan_rate(n_months),
+            self.max_loan(n_months, years)?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use date::Date;
+    use regulated::RateHistory;
+
+    fn pel(opening: Date, rate: f32) -> HomeSavingsPlan {
+        let account = RegulatedAccount::new(AccountKind::Pel, 1000, opening)
+            .unwrap()
+            .with_rates(RateHistory::new(vec![(opening, rate)]));
+        HomeSavingsPlan::new(account, 200).unwrap()
+    }
+
+    #[test]
+    fn test_minimums() {
+        let opening = Date::new(2019, 3, 1).unwrap();
+        let account = |kind, balance| RegulatedAccount::new(kind, balance, opening).unwrap();
+        assert!(HomeSavingsPlan::new(account(AccountKind::Pel, 100), 100).is_err());
+        assert!(HomeSavingsPlan::new(account(AccountKind::Pel, 1000), 40).is_err());
+        assert!(HomeSavingsPlan::new(account(AccountKind::Cel, 1000), 0).is_ok());
+        assert!(HomeSavingsPlan::new(account(AccountKind::Cel, 1000), 50).is_err());
+        assert!(HomeSavingsPlan::new(account(AccountKind::LivretA, 1000), 100).is_err());
+        let plan = pel(opening, 0.01);
+        assert!(plan.savings(36).is_err());
+        assert!(plan.savings(121).is_err());
+        assert!(plan.max_loan(60, 20).is_err());
+    }
+
+    #[test]
+    fn test_loan() {
+        let plan = pel(Date::new(2017, 1, 1).unwrap(), 0.01);
+        let interest = plan.savings(60).unwrap().interest;
+        assert!((plan.tax_on_interest(60).unwrap() - interest * 0.172).abs() < 1e-9);
+        let loan = plan.loan(60, 10).unwrap();
+        assert!((loan.interest_rate_year - 0.022).abs() < 1e-6);
+        let loan_interest = loan.interest_at(loan.periods()).unwrap();
+        assert!(loan_interest <= interest * 2.5 + 1_f64);
+        assert!(loan_interest > interest * 2.5 - 10_f64);
+
+        // The same plan opened in 2018 is taxed at the flat tax, and the loan is capped
+        let plan = pel(Date::new(2018, 1, 1).unwrap(), 0.05);
+        let interest = plan.savings(120).unwrap().interest;
+        assert!((plan.tax_on_interest(120).unwrap() - interest * 0.3).abs() < 1e-6);
+        assert_eq!(92_000, plan.loan(120, 2).unwrap().capital);
+    }
+}
@@ -0,0 +1,267 @@
+use csv_file::records;
+use date::Date;
+use error::{check_duration, check_rate, HomeCalcError};
+use loan::{annuity, Loan};
+use std::fs;
+use std::path::Path;
+
+/// A series of published values of a reference rate (e.g. Euribor 3 months)
+pub struct RateIndex {
+    pub values: Vec<(Date, f32)>,
+}
+
+impl RateIndex {
+    /// Returns an index given its published values, in any order
+    ///
+    /// # Arguments
+    /// * `values` - publication dates and rates
+    pub fn new(mut values: Vec<(Date, f32)>) -> RateIndex {
+        values.sort_by_key(|&(date, _)| date);
+        RateIndex { values }
+    }
+
+    /// Return the last date of the series
+    pub fn last_date(&self) -> Option<Date> {
+        self.values.last().map(|&(date, _)| date)
+    }
+
+    /// Return the rate published at a date
+    ///
+    /// The last value published before the date is used and the series is extrapolated flat
+    /// forward after its end. There is no rate before the first publication.
+    ///
+    /// # Arguments
+    /// * `date` - the date
+    pub fn rate_at(&self, date: &Date) -> Option<f32> {
+        self.values
+            .iter()
+            .take_while(|&&(published, _)| published <= *date)
+            .last()
+            .map(|&(_, rate)| rate)
+    }
+}
+
+/// Load an index series from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_rate_index(path: &Path) -> Result<RateIndex, HomeCalcError> {
+    parse_rate_index(&fs::read_to_string(path)?)
+}
+
+/// Parse an index series from CSV lines of `date;rate`, the rate being in percent
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_rate_index(content: &str) -> Result<RateIndex, HomeCalcError> {
+    let mut values = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let rate = record.number(1).ok_or_else(|| record.error("invalid rate"))?;
+        values.push((date, (rate / 100_f64) as f32));
+    }
+    Ok(RateIndex::new(values))
+}
+
+/// An installment of an indexed loan
+#[derive(Debug, Clone)]
+pub struct IndexedInstallment {
+    pub number: u32,
+    pub due: Date,
+    pub interest_rate_year: f32,
+    pub term_price: f64,
+    pub interest: f64,
+    pub capital: f64,
+    pub balance: f64,
+    pub extrapolated: bool,
+}
+
+/// A variable rate loan indexed on a reference rate plus a margin
+pub struct IndexedLoan {
+    pub loan: Loan,
+    pub start: Date,
+    pub margin: f32,
+    pub reset_every: u32,
+    pub cap: Option<f32>,
+}
+
+impl IndexedLoan {
+    /// Returns an indexed loan given all its parameters, or an error for an invalid parameter
+    ///
+    /// The rate of the loan applies until the first reset. The installments are due on the same
+    /// day of the month, so the periodicity must divide the year into whole months.
+    ///
+    /// # Arguments
+    /// * `loan` - the loan at its initial rate
+    /// * `start` - the date the funds are released, the first installment is due one term later
+    /// * `margin` - margin of the bank added to the index
+    /// * `reset_every` - number of months between rate resets
+    /// * `cap` - maximum move of the rate, both ways, from the initial rate
+    ///
+    /// # Example
+    /// ```
+    /// // Euribor + 1% revised every year, capped at +/- 1%
+    /// let loan = Loan::new(20, 12, 0.015, 200_000)?;
+    /// let indexed = IndexedLoan::new(loan, start, 0.01, 12, Some(0.01))?;
+    /// ```
+    pub fn new(
+        loan: Loan,
+        start: Date,
+        margin: f32,
+        reset_every: u32,
+        cap: Option<f32>,
+    ) -> Result<IndexedLoan, HomeCalcError> {
+        if 12 % loan.period != 0 {
+            return Err(HomeCalcError::InvalidPeriodicity(loan.period));
+        }
+        check_rate("margin", margin)?;
+        check_duration("months between resets", reset_every)?;
+        if let Some(cap) = cap {
+            check_rate("cap", cap)?;
+        }
+        Ok(IndexedLoan {
+            loan,
+            start,
+            margin,
+            reset_every,
+            cap,
+        })
+    }
+
+    /// Return the due date of an installment
+    ///
+    /// # Arguments
+    /// * `number` - the installment number, starting at 1
+    pub fn due_date(&self, number: u32) -> Date {
+        self.start.add_months(number * 12 / self.loan.period as u32)
+    }
+
+    /// Return the number of installments between two rate resets
+    pub fn reset_terms(&self) -> u32 {
+        (self.reset_every * self.loan.period as u32 / 12).max(1)
+    }
+
+    /// Return the rate applied from a reset date, the index plus the margin bounded by the cap
+    ///
+    /// # Arguments
+    /// * `index` - the reference rate series
+    /// * `date` - the reset date
+    pub fn rate_at(&self, index: &RateIndex, date: &Date) -> f32 {
+        let initial = self.loan.interest_rate_year;
+        let rate = index
+            .rate_at(date)
+            .map(|value| value + self.margin)
+            .unwrap_or(initial);
+        let rate = match self.cap {
+            Some(cap) => rate.max(initial - cap).min(initial + cap),
+            None => rate,
+        };
+        rate.max(0_f32)
+    }
+
+    /// Return every installment of the loan driven by an index series
+    ///
+    /// # Arguments
+    /// * `index` - the reference rate series
+    pub fn installments(&self, index: &RateIndex) -> Vec<IndexedInstallment> {
+        let periods = self.loan.periods();
+        let reset_terms = self.reset_terms();
+        let mut balance = self.loan.capital as f64;
+        let mut rate = self.loan.interest_rate_year;
+        let mut term_price = self.loan.term_price();
+        let mut extrapolated = false;
+        let mut installments = Vec::new();
+        for number in 1..=periods {
+            if number > 1 && (number - 1) % reset_terms == 0 {
+                let reset_date = self.due_date(number - 1);
+                rate = self.rate_at(index, &reset_date);
+                extrapolated = index.last_date().is_none_or(|last| reset_date > last);
+                term_price = annuity(
+                    balance,
+                    rate as f64 / self.loan.period as f64,
+                    periods - number + 1,
+                );
+            }
+            let interest = (balance * rate as f64 / self.loan.period as f64 * 100_f64).round()
+                / 100_f64;
+            let paid = if number == periods {
+                balance + interest
+            } else {
+                term_price
+            };
+            balance -= paid - interest;
+            installments.push(IndexedInstallment {
+                number,
+                due: self.due_date(number),
+                interest_rate_year: rate,
+                term_price: paid,
+                interest,
+                capital: paid - interest,
+                balance: (balance * 100_f64).round() / 100_f64,
+                extrapolated,
+            });
+        }
+        installments
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use date::date;
+
+    fn index() -> RateIndex {
+        parse_rate_index("date;euribor\n2019-01-01;0,5\n2020-01-01;1,5\n2021-01-01;3\n").unwrap()
+    }
+
+    #[test]
+    fn test_rate_index() {
+        let index = index();
+        assert_eq!(None, index.rate_at(&date(2018, 6, 1)));
+        assert_eq!(Some(0.005), index.rate_at(&date(2019, 1, 1)));
+        assert_eq!(Some(0.005), index.rate_at(&date(2019, 12, 31)));
+        assert_eq!(Some(0.015), index.rate_at(&date(2020, 1, 1)));
+        assert_eq!(Some(0.03), index.rate_at(&date(2030, 1, 1)));
+        assert_eq!(Some(date(2021, 1, 1)), index.last_date());
+        assert!(parse_rate_index("2019-01-01;abc").is_err());
+        // The initial rate of the loan is kept before the first publication
+        let loan = Loan::new(20, 12, 0.015_f32, 200_000).unwrap();
+        let indexed = IndexedLoan::new(loan.clone(), date(2018, 1, 1), 0.01, 12, None).unwrap();
+        assert_eq!(0.015, indexed.rate_at(&index, &date(2018, 6, 1)));
+        assert!(IndexedLoan::new(loan, date(2019, 1, 1), 0.01, 0, None).is_err());
+        let weekly = Loan::new(20, 52, 0.015_f32, 200_000).unwrap();
+        assert!(IndexedLoan::new(weekly, date(2019, 1, 1), 0.01, 12, None).is_err());
+    }
+
+    #[test]
+    fn test_constant_index_matches_fixed_loan() {
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        let index = RateIndex::new(vec![(date(2019, 1, 1), 0.019)]);
+        let indexed = IndexedLoan::new(loan, date(2019, 1, 1), 0.01, 12, None).unwrap();
+        let installments = indexed.installments(&index);
+        assert_eq!(240, installments.len());
+        assert_eq!(date(2019, 2, 1), installments[0].due);
+        assert!((installments[120].term_price - 494.64).abs() <= 0.01);
+        assert!(installments.last().unwrap().balance.abs() < 0.01);
+    }
+
+    #[test]
+    fn test_installments() {
+        let loan = Loan::new(20, 12, 0.015_f32, 200_000).unwrap();
+        let indexed = IndexedLoan::new(loan, date(2019, 1, 1), 0.01, 12, Some(0.02)).unwrap();
+        let installments = indexed.installments(&index());
+        let rate = |number: usize| installments[number - 1].interest_rate_year;
+        assert!((rate(12) - 0.015).abs() < 1e-6);
+        assert!((rate(13) - 0.025).abs() < 1e-6);
+        assert!(installments[12].term_price > installments[11].term_price);
+        assert!((rate(25) - 0.035).abs() < 1e-6);
+        assert!(!installments[24].extrapolated);
+        assert!((rate(37) - 0.035).abs() < 1e-6);
+        assert!(installments[36].extrapolated);
+        assert!(installments.last().unwrap().balance.abs() < 0.01);
+    }
+}
@@ -0,0 +1,199 @@
+use csv_file::records;
+use error::HomeCalcError;
+use std::fs;
+use std::path::Path;
+
+/// An inflation assumption, as yearly rates from the start of a computation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inflation {
+    /// Inflation of each year, the last rate is kept after the end of the series
+    pub rates: Vec<f32>,
+}
+
+impl Inflation {
+    /// Returns an inflation given the rates of the next years, or an error for an invalid rate
+    ///
+    /// # Arguments
+    /// * `rates` - inflation of each year, starting with the first year of the computation
+    ///
+    /// # Example
+    /// ```
+    /// // 4% next year, 3% the year after then 2% every year
+    /// let inflation = Inflation::new(vec![0.04, 0.03, 0.02])?;
+    /// ```
+    pub fn new(rates: Vec<f32>) -> Result<Inflation, HomeCalcError> {
+        if rates.is_empty() {
+            return Err(HomeCalcError::InvalidValue {
+                name: "inflation".to_string(),
+                value: String::new(),
+            });
+        }
+        if let Some(&rate) = rates.iter().find(|r| !r.is_finite() || **r <= -1_f32) {
+            return Err(HomeCalcError::InvalidRate {
+                name: "inflation",
+                value: rate,
+            });
+        }
+        Ok(Inflation { rates })
+    }
+
+    /// Returns a constant inflation
+    ///
+    /// # Arguments
+    /// * `rate` - inflation by year
+    pub fn constant(rate: f32) -> Result<Inflation, HomeCalcError> {
+        Inflation::new(vec![rate])
+    }
+
+    /// Return the inflation of a year
+    ///
+    /// # Arguments
+    /// * `year` - the year, starting at 0
+    pub fn rate_of_year(&self, year: u32) -> f32 {
+        *self
+            .rates
+            .get(year as usize)
+            .unwrap_or_else(|| self.rates.last().unwrap())
+    }
+
+    /// Return the price level at the end of a period, prices being 1 at the start
+    ///
+    /// # Arguments
+    /// * `period` - the period
+    /// * `periodicity` - number of periods by year
+    pub fn deflator(&self, period: u32, periodicity: u8) -> f64 {
+        let years = period / periodicity as u32;
+        let rest = (period % periodicity as u32) as f64 / periodicity as f64;
+        (0..years).fold(1_f64, |level, year| {
+            level * (1_f64 + self.rate_of_year(year) as f64)
+        }) * (1_f64 + self.rate_of_year(years) as f64).powf(rest)
+    }
+
+    /// Return an amount paid at the end of a period in money of the start
+    ///
+    /// # Arguments
+    /// * `amount` - the nominal amount
+    /// * `period` - the period
+    /// * `periodicity` - number of periods by year
+    pub fn real(&self, amount: f64, period: u32, periodicity: u8) -> f64 {
+        amount / self.deflator(period, periodicity)
+    }
+}
+
+/// Load an inflation series from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_inflation(path: &Path) -> Result<Inflation, HomeCalcError> {
+    parse_inflation(&fs::read_to_string(path)?)
+}
+
+/// Parse an inflation series from CSV lines of `year;rate`, the rate being in percent
+///
+/// The first year of the file is the first year of the computation.
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_inflation(content: &str) -> Result<Inflation, HomeCalcError> {
+    let mut rates = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let year = match record.number(0) {
+            Some(year) => year,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid year").into()),
+        };
+        let rate = record.number(1).ok_or_else(|| record.error("invalid rate"))?;
+        rates.push((year as i64, (rate / 100_f64) as f32));
+    }
+    rates.sort_by_key(|&(year, _)| year);
+    Inflation::new(rates.into_iter().map(|(_, rate)| rate).collect())
+}
+
+/// Load the yearly variations of an index from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_index(path: &Path) -> Result<Inflation, HomeCalcError> {
+    parse_index(&fs::read_to_string(path)?)
+}
+
+/// Parse the yearly variations of an index (e.g. rents or home prices) from CSV lines of
+/// `date;value`
+///
+/// The first date of the file is the start of the computation, the variation of each year
+/// compares the last values known at two anniversaries.
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_index(content: &str) -> Result<Inflation, HomeCalcError> {
+    let mut values = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let value = record
+            .number(1)
+            .filter(|&value| value > 0_f64)
+            .ok_or_else(|| record.error("invalid index value"))?;
+        values.push((date, value));
+    }
+    values.sort_by_key(|&(date, _)| date);
+    let (start, last) = match (values.first(), values.last()) {
+        (Some(&(start, _)), Some(&(last, _))) => (start, last),
+        _ => return Inflation::new(Vec::new()),
+    };
+    let value_at = |date| {
+        values
+            .iter()
+            .take_while(|&&(at, _)| at <= date)
+            .last()
+            .map_or(0_f64, |&(_, value)| value)
+    };
+    let mut rates = Vec::new();
+    let mut year = 1;
+    while start.add_months(12 * year) <= last {
+        let previous = value_at(start.add_months(12 * (year - 1)));
+        rates.push((value_at(start.add_months(12 * year)) / previous - 1_f64) as f32);
+        year += 1;
+    }
+    Inflation::new(rates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deflator() {
+        let inflation = Inflation::constant(0.02).unwrap();
+        assert_eq!(1_f64, inflation.deflator(0, 12));
+        assert!((inflation.deflator(24, 12) - 1.0404).abs() < 1e-6);
+        assert!((inflation.deflator(6, 12) - 1.02_f64.sqrt()).abs() < 1e-6);
+        assert!((inflation.real(1040.4, 24, 12) - 1000_f64).abs() < 1e-4);
+        assert!(Inflation::constant(-1_f32).is_err());
+    }
+
+    #[test]
+    fn test_parse_inflation() {
+        let inflation = parse_inflation("year;inflation\n2021;3\n2020;4\n2022;2\n").unwrap();
+        assert!((inflation.rate_of_year(0) - 0.04).abs() < 1e-6);
+        assert!((inflation.rate_of_year(2) - 0.02).abs() < 1e-6);
+        assert!((inflation.rate_of_year(10) - 0.02).abs() < 1e-6);
+        assert!(parse_inflation("year;inflation\n").is_err());
+        assert!(parse_inflation("2020;abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_index() {
+        let index = parse_index(
+            "date;value\n2022-01-15;100\n2022-04-15;102\n2023-01-15;104\n2023-07-15;106\n\
+             2024-01-15;107,12\n",
+        ).unwrap();
+        assert_eq!(2, index.rates.len());
+        assert!((index.rate_of_year(0) - 0.04).abs() < 1e-6);
+        assert!((index.rate_of_year(1) - 0.03).abs() < 1e-6);
+        assert!(parse_index("date;value\n2022-01-15;100\n").is_err());
+    }
+}
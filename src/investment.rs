@@ -1,13 +1,108 @@
+use compounding::Compounding;
+use contribution::{ContributionPlan, Timing};
+use error::{check_duration, check_periodicity, HomeCalcError};
+use fees::Fees;
+use portfolio::Portfolio;
+use solver::bisect;
+use tax::Wrapper;
+use withdrawal::WithdrawalPlan;
+use yield_curve::YieldCurve;
+
+/// Balance under which an investment is considered depleted
+const DEPLETED: f64 = 0.005;
+
 /// An investment
+#[derive(Clone)]
 pub struct Investment {
     pub capital: u32,
     pub periodicity: u8,
     pub yield_rate: f32,
     pub regular_addition: u32,
+    pub plan: ContributionPlan,
+    pub withdrawals: Option<WithdrawalPlan>,
+    pub wrapper: Option<Wrapper>,
+    pub fees: Fees,
+    pub portfolio: Option<Portfolio>,
+    pub timing: Timing,
+    /// How often the interest is compounded, at each period when none
+    pub compounding: Option<Compounding>,
+    /// How the yield rate evolves over time, constant when none
+    pub curve: Option<YieldCurve>,
+}
+
+/// The state of an investment at the end of a period
+struct State {
+    capital: f64,
+    /// The amount held in each asset of the portfolio, or the capital without portfolio
+    holdings: Vec<f64>,
+    basis: f64,
+    withdrawal: f64,
+    withdrawn: f64,
+    tax: f64,
+    fees: f64,
+    /// The allowance on the gains used by the withdrawals of the current year
+    allowance_used: f64,
+}
+
+/// A period of the schedule of an investment
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleRow {
+    /// The period, starting at 1
+    pub period: u32,
+    /// Capital at the start of the period
+    pub opening: f64,
+    pub contribution: f64,
+    /// Return of the holdings over the period
+    pub growth: f64,
+    /// Entry and management fees of the period
+    pub fees: f64,
+    pub withdrawal: f64,
+    /// Tax on the withdrawal of the period
+    pub tax: f64,
+    /// Capital at the end of the period
+    pub closing: f64,
+    /// Capital left at the end of the period once everything is withdrawn and the tax is paid
+    pub after_tax: f64,
+}
+
+/// An iterator over the periods of an investment, computed step by step
+pub struct Schedule<'a> {
+    invest: &'a Investment,
+    state: Option<State>,
+    period: u32,
+}
+
+impl<'a> Iterator for Schedule<'a> {
+    type Item = ScheduleRow;
+
+    fn next(&mut self) -> Option<ScheduleRow> {
+        let state = self.state.take()?;
+        self.period += 1;
+        let period = self.period;
+        let (opening, tax, fees) = (state.capital, state.tax, state.fees);
+        let contribution = self.invest.contribution_at(period);
+        let returns = self.invest.expected_returns(period);
+        let state = self.invest.step(state, period, &returns, contribution);
+        let fees = state.fees - fees;
+        let row = ScheduleRow {
+            period,
+            opening,
+            contribution,
+            growth: state.capital - opening - contribution + fees + state.withdrawal,
+            fees,
+            withdrawal: state.withdrawal,
+            tax: state.tax - tax,
+            closing: state.capital,
+            after_tax: state.capital
+                - self.invest.tax_on(state.capital - state.basis, period, state.allowance_used),
+        };
+        self.state = Some(state);
+        Some(row)
+    }
 }
 
 impl Investment {
-    /// Returns an investment given all its parameters
+    /// Returns an investment given all its parameters, or an error for an invalid parameter
     ///
     /// # Aguments
     ///
@@ -19,34 +114,189 @@ impl Investment {
     ///
     /// ```
     /// use investment::Investment;
-    /// let invest_10k_1p_4percent = Investment::new(10_000, 1, 4_f32, 0);
+    /// let invest_10k_1p_4percent = Investment::new(10_000, 1, 0.04, 0)?;
     /// ```
     pub fn new(
         capital: u32,
         periodicity: u8,
         yield_rate: f32,
         regular_addition: u32,
-    ) -> Investment {
-        Investment {
+    ) -> Result<Investment, HomeCalcError> {
+        check_periodicity(periodicity)?;
+        if !yield_rate.is_finite() || yield_rate <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "yield rate",
+                value: yield_rate,
+            });
+        }
+        Ok(Investment {
             capital,
             periodicity,
             yield_rate,
             regular_addition,
-        }
+            plan: ContributionPlan::default(),
+            withdrawals: None,
+            wrapper: None,
+            fees: Fees::default(),
+            portfolio: None,
+            timing: Timing::End,
+            compounding: None,
+            curve: None,
+        })
+    }
+
+    /// Returns the investment with its regular addition following a contribution plan
+    ///
+    /// # Arguments
+    /// * `plan` - the contribution plan
+    ///
+    /// # Example
+    /// ```
+    /// let plan = ContributionPlan::new(0.02)?.with_lump_sum(60, 10_000);
+    /// let invest = Investment::new(10_000, 12, 0.04, 200)?.with_plan(plan);
+    /// ```
+    pub fn with_plan(mut self, plan: ContributionPlan) -> Investment {
+        self.plan = plan;
+        self
+    }
+
+    /// Returns the investment with a drawdown phase
+    ///
+    /// Contributions planned during the drawdown phase are still added before each withdrawal.
+    ///
+    /// # Arguments
+    /// * `withdrawals` - the withdrawal plan
+    ///
+    /// # Example
+    /// ```
+    /// let withdrawals = WithdrawalPlan::new(121, Withdrawal::Fixed(800))?;
+    /// let invest = Investment::new(100_000, 12, 0.04, 0)?.with_withdrawals(withdrawals);
+    /// ```
+    pub fn with_withdrawals(mut self, withdrawals: WithdrawalPlan) -> Investment {
+        self.withdrawals = Some(withdrawals);
+        self
+    }
+
+    /// Returns the investment held in an account whose gains are taxed on withdrawal
+    ///
+    /// # Arguments
+    /// * `wrapper` - the account holding the investment
+    pub fn with_wrapper(mut self, wrapper: Wrapper) -> Investment {
+        self.wrapper = Some(wrapper);
+        self
+    }
+
+    /// Returns the investment in a fund charging fees
+    ///
+    /// Entry fees are charged on every contribution, the initial capital being already invested,
+    /// and management fees are taken from the balance at the end of each period.
+    ///
+    /// # Arguments
+    /// * `fees` - the fees of the fund
+    pub fn with_fees(mut self, fees: Fees) -> Investment {
+        self.fees = fees;
+        self
+    }
+
+    /// Returns the investment spread over a portfolio of asset classes
+    ///
+    /// The yield rate of the investment is replaced by the yields of the assets, the initial
+    /// capital follows the target allocation and the contributions go to the underweight assets.
+    ///
+    /// # Arguments
+    /// * `portfolio` - the portfolio
+    pub fn with_portfolio(mut self, portfolio: Portfolio) -> Investment {
+        self.portfolio = Some(portfolio);
+        self
+    }
+
+    /// Returns the investment with its contributions added at the start or the end of periods
+    ///
+    /// # Arguments
+    /// * `timing` - when the contributions are added
+    pub fn with_timing(mut self, timing: Timing) -> Investment {
+        self.timing = timing;
+        self
+    }
+
+    /// Returns the investment with its interest compounded at another frequency than its periods
+    ///
+    /// # Arguments
+    /// * `compounding` - how often the interest is compounded
+    ///
+    /// # Example
+    /// ```
+    /// // A Livret A fed every month, whose interest is compounded once a year
+    /// let invest = Investment::new(1000, 12, 0.03, 100)?
+    ///     .with_compounding(Compounding::Periodic(1));
+    /// ```
+    pub fn with_compounding(mut self, compounding: Compounding) -> Investment {
+        self.compounding = Some(compounding);
+        self
+    }
+
+    /// Returns the investment with a yield changing over time, its yield rate being the initial
+    /// one
+    ///
+    /// The yields of a portfolio don't follow the curve.
+    ///
+    /// # Arguments
+    /// * `curve` - the yield curve
+    ///
+    /// # Example
+    /// ```
+    /// // 6% for 10 years, then 3%
+    /// let curve = YieldCurve::phases(vec![(121, 0.03)])?;
+    /// let invest = Investment::new(10_000, 12, 0.06, 200)?.with_curve(curve);
+    /// ```
+    pub fn with_curve(mut self, curve: YieldCurve) -> Investment {
+        self.curve = Some(curve);
+        self
     }
 
     fn yield_rate_period(&self) -> f32 {
         self.yield_rate / self.periodicity as f32
     }
 
+    /// Return the return of a period for a yearly rate, given the compounding
+    fn period_rate_of(&self, yield_rate: f32) -> f64 {
+        match self.compounding {
+            Some(compounding) => compounding.period_rate(yield_rate, self.periodicity),
+            None => yield_rate as f64 / self.periodicity as f64,
+        }
+    }
+
+    fn period_rate(&self) -> f64 {
+        match self.compounding {
+            Some(_) => self.period_rate_of(self.yield_rate),
+            None => self.yield_rate_period() as f64,
+        }
+    }
+
     fn capital_principal(&self, n_period: u32) -> f64 {
-        self.capital as f64 * (1_f64 + self.yield_rate_period() as f64).powf(n_period as f64)
+        self.capital as f64 * (1_f64 + self.period_rate()).powf(n_period as f64)
     }
 
     fn capital_additions(&self, n_period: u32) -> f64 {
-        self.regular_addition as f64
-            * (((1_f64 + self.yield_rate_period() as f64).powf(n_period as f64) - 1_f64)
-                / self.yield_rate_period() as f64)
+        let rate = self.period_rate();
+        if rate == 0_f64 {
+            return self.regular_addition as f64 * n_period as f64;
+        }
+        let additions =
+            self.regular_addition as f64 * (((1_f64 + rate).powf(n_period as f64) - 1_f64) / rate);
+        match self.timing {
+            Timing::End => additions,
+            Timing::Start => additions * (1_f64 + rate),
+        }
+    }
+
+    /// Return the contribution added at the end of a period
+    ///
+    /// # Arguments
+    /// * `period` - the period, starting at 1
+    pub fn contribution_at(&self, period: u32) -> f64 {
+        self.plan
+            .contribution_at(self.regular_addition, self.periodicity, period)
     }
 
     /// Return the capital at a moment for an investment
@@ -58,20 +308,467 @@ impl Investment {
     /// ```
     /// // We have an 10k investment with perodicity of 12 and interest rate of 4%
     /// // Get the capital at 2 years with a regular addition of 100 per period
-    /// let invest_10k_1p_4percent_100a = Investment::new(10_000, 12, 4_f32, 100);
+    /// let invest_10k_1p_4percent_100a = Investment::new(10_000, 12, 0.04, 100)?;
     /// let capital_at_2y = loan.capital_at(24);
     /// ```
     pub fn capital_at(&self, n_period: u32) -> f64 {
-        self.capital_principal(n_period) + self.capital_additions(n_period)
+        if self.plan.is_constant()
+            && self.withdrawals.is_none()
+            && self.fees.is_free()
+            && self.portfolio.is_none()
+            && self.curve.is_none()
+        {
+            return self.capital_principal(n_period) + self.capital_additions(n_period);
+        }
+        self.simulate(n_period).capital
+    }
+
+    /// Return the schedule of the investment, an endless iterator over its periods
+    ///
+    /// Each period is computed from the previous one, with the contribution of the plan.
+    ///
+    /// # Example
+    /// ```
+    /// // The capital at the end of each year for 10 years
+    /// let capitals: Vec<f64> = invest
+    ///     .schedule()
+    ///     .take(120)
+    ///     .filter(|row| row.period % 12 == 0)
+    ///     .map(|row| row.closing)
+    ///     .collect();
+    /// ```
+    pub fn schedule(&self) -> Schedule<'_> {
+        Schedule {
+            invest: self,
+            state: Some(self.initial_state()),
+            period: 0,
+        }
+    }
+
+    /// Compute the investment period by period up to the end of a period
+    fn simulate(&self, n_period: u32) -> State {
+        (1..=n_period).fold(self.initial_state(), |state, period| {
+            let returns = self.expected_returns(period);
+            self.step(state, period, &returns, self.contribution_at(period))
+        })
+    }
+
+    /// Return the return of a period, given the yield curve
+    ///
+    /// # Arguments
+    /// * `period` - the period, starting at 1
+    fn period_rate_at(&self, period: u32) -> f64 {
+        match self.curve {
+            Some(ref curve) => {
+                self.period_rate_of(curve.yield_at(self.yield_rate, self.periodicity, period))
+            }
+            None => self.period_rate(),
+        }
+    }
+
+    /// Return the expected return of each asset for a period
+    fn expected_returns(&self, period: u32) -> Vec<f64> {
+        match self.portfolio {
+            Some(ref portfolio) => portfolio
+                .assets
+                .iter()
+                .map(|asset| self.period_rate_of(asset.yield_rate))
+                .collect(),
+            None => vec![self.period_rate_at(period)],
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        State {
+            capital: self.capital as f64,
+            holdings: match self.portfolio {
+                Some(ref portfolio) => portfolio.split(self.capital as f64),
+                None => vec![self.capital as f64],
+            },
+            basis: self.capital as f64,
+            withdrawal: 0_f64,
+            withdrawn: 0_f64,
+            tax: 0_f64,
+            fees: 0_f64,
+            allowance_used: 0_f64,
+        }
+    }
+
+    /// Compute the state at the end of a period from the state at its start
+    fn step(&self, state: State, period: u32, returns: &[f64], contribution: f64) -> State {
+        let entry_fee = self.fees.entry_fee(contribution);
+        let holdings = match self.timing {
+            Timing::Start => self.allocate(state.holdings, contribution - entry_fee),
+            Timing::End => state.holdings,
+        };
+        let holdings: Vec<f64> = holdings
+            .iter()
+            .zip(returns)
+            .map(|(holding, period_return)| holding * (1_f64 + period_return))
+            .collect();
+        let grown: f64 = holdings.iter().sum();
+        let management_fee = self.fees.management_fee(grown, self.periodicity);
+        let holdings = scale(holdings, grown - management_fee, grown);
+        let holdings = match self.timing {
+            Timing::Start => holdings,
+            Timing::End => self.allocate(holdings, contribution - entry_fee),
+        };
+        let fees = management_fee + entry_fee;
+        let capital: f64 = holdings.iter().sum();
+        let basis = state.basis + contribution;
+        let withdrawal = match self.withdrawals {
+            Some(ref plan) => plan
+                .planned_at(self.periodicity, period, capital)
+                .min(capital.max(0_f64)),
+            None => 0_f64,
+        };
+        // The withdrawal takes a share of the invested amount and of the gains
+        let withdrawn_basis = if capital > 0_f64 {
+            basis * withdrawal / capital
+        } else {
+            0_f64
+        };
+        let holdings = scale(holdings, capital - withdrawal, capital);
+        // The allowance on the gains is renewed at the start of each year
+        let allowance_used = if (period - 1).is_multiple_of(self.periodicity as u32) {
+            0_f64
+        } else {
+            state.allowance_used
+        };
+        let gain = withdrawal - withdrawn_basis;
+        State {
+            capital: capital - withdrawal,
+            holdings: match self.portfolio {
+                Some(ref portfolio) => portfolio.rebalance(holdings, period),
+                None => holdings,
+            },
+            basis: basis - withdrawn_basis,
+            withdrawal,
+            withdrawn: state.withdrawn + withdrawal,
+            tax: state.tax + self.tax_on(gain, period, allowance_used),
+            fees: state.fees + fees,
+            allowance_used: allowance_used + self.allowance_used_by(gain, period, allowance_used),
+        }
+    }
+
+    /// Add an amount to the holdings, along the portfolio when there is one
+    fn allocate(&self, holdings: Vec<f64>, amount: f64) -> Vec<f64> {
+        match self.portfolio {
+            Some(ref portfolio) => portfolio.allocate(&holdings, amount),
+            None => vec![holdings[0] + amount],
+        }
+    }
+
+    /// Return the capital at the start and at the end of each period for a sequence of returns
+    ///
+    /// The yield rate of the investment is ignored, contributions, fees and withdrawals apply.
+    /// Every asset of a portfolio gets the same return.
+    ///
+    /// # Arguments
+    /// * `returns` - the return of each period
+    pub fn capital_path(&self, returns: &[f64]) -> Vec<f64> {
+        let n_asset = self.portfolio.as_ref().map_or(1, |p| p.assets.len());
+        let returns: Vec<Vec<f64>> = returns.iter().map(|&r| vec![r; n_asset]).collect();
+        self.capital_path_by_asset(&returns)
+    }
+
+    /// Return the capital at the start and at the end of each period for a sequence of returns
+    /// of each asset
+    ///
+    /// # Arguments
+    /// * `returns` - the return of each asset of the portfolio (or of the investment) by period
+    pub fn capital_path_by_asset(&self, returns: &[Vec<f64>]) -> Vec<f64> {
+        let mut state = self.initial_state();
+        let mut path = Vec::with_capacity(returns.len() + 1);
+        path.push(state.capital);
+        for (index, period_returns) in returns.iter().enumerate() {
+            let period = index as u32 + 1;
+            state = self.step(state, period, period_returns, self.contribution_at(period));
+            path.push(state.capital);
+        }
+        path
+    }
+
+    fn tax_on(&self, gain: f64, period: u32, allowance_used: f64) -> f64 {
+        match self.wrapper {
+            Some(wrapper) => wrapper.tax_on_gain(gain, self.years_held(period), allowance_used),
+            None => 0_f64,
+        }
+    }
+
+    /// Return the part of the yearly allowance a gain withdrawn at a period uses
+    fn allowance_used_by(&self, gain: f64, period: u32, allowance_used: f64) -> f64 {
+        match self.wrapper {
+            Some(wrapper) => {
+                let left = (wrapper.allowance(self.years_held(period)) - allowance_used).max(0_f64);
+                gain.max(0_f64).min(left)
+            }
+            None => 0_f64,
+        }
+    }
+
+    fn years_held(&self, period: u32) -> f64 {
+        period as f64 / self.periodicity as f64
+    }
+
+    /// Return the capital at the end of a sequence of periods with additional contributions
+    ///
+    /// Each addition is added at the end of its period on top of the contribution plan, e.g.
+    /// the deposits overflowing the ceiling of a savings account.
+    ///
+    /// # Arguments
+    /// * `additions` - the additional contribution of each period
+    pub fn capital_with_additions(&self, additions: &[f64]) -> f64 {
+        additions
+            .iter()
+            .enumerate()
+            .fold(self.initial_state(), |state, (index, addition)| {
+                let period = index as u32 + 1;
+                let returns = self.expected_returns(period);
+                self.step(state, period, &returns, self.contribution_at(period) + addition)
+            }).capital
+    }
+
+    /// Return the cash flows of the investor up to a period, the capital being sold at its end
+    ///
+    /// The capital and the contributions are outflows, the withdrawals net of tax and the capital
+    /// left after tax at the last period are inflows.
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    ///
+    /// # Example
+    /// ```
+    /// // The money-weighted return of the investment by period
+    /// let rate = cash_flow::irr(&invest.cash_flows(120))?;
+    /// ```
+    pub fn cash_flows(&self, n_period: u32) -> Vec<f64> {
+        let mut state = self.initial_state();
+        let mut flows = vec![-(self.capital as f64)];
+        for period in 1..=n_period {
+            let tax = state.tax;
+            let returns = self.expected_returns(period);
+            state = self.step(state, period, &returns, self.contribution_at(period));
+            flows.push(state.withdrawal - (state.tax - tax) - self.contribution_at(period));
+        }
+        let tax = self.tax_on(state.capital - state.basis, n_period, state.allowance_used);
+        flows[n_period as usize] += state.capital - tax;
+        flows
+    }
+
+    /// Return the amount withdrawn at the end of a period, bounded by the balance
+    ///
+    /// # Arguments
+    /// * `period` - the period, starting at 1
+    pub fn withdrawal_at(&self, period: u32) -> f64 {
+        self.simulate(period).withdrawal
+    }
+
+    /// Total of withdrawals for a number of periods
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    pub fn withdrawals_total(&self, n_period: u32) -> f64 {
+        self.simulate(n_period).withdrawn
+    }
+
+    /// Total of the taxes paid on withdrawals for a number of periods
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    pub fn taxes_total(&self, n_period: u32) -> f64 {
+        self.simulate(n_period).tax
+    }
+
+    /// Total of the fees paid for a number of periods
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    pub fn fees_total(&self, n_period: u32) -> f64 {
+        self.simulate(n_period).fees
+    }
+
+    /// Return the capital the investment would reach without fees
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    pub fn gross_capital_at(&self, n_period: u32) -> f64 {
+        Investment {
+            fees: Fees::default(),
+            ..self.clone()
+        }.capital_at(n_period)
+    }
+
+    /// Return the capital left once everything is withdrawn and the tax is paid
+    ///
+    /// The gross capital is returned when the investment has no wrapper.
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    ///
+    /// # Example
+    /// ```
+    /// let invest = Investment::new(10_000, 12, 0.04, 100)?.with_wrapper(Wrapper::Pea);
+    /// let net_at_6y = invest.after_tax_capital_at(72);
+    /// ```
+    pub fn after_tax_capital_at(&self, n_period: u32) -> f64 {
+        if self.wrapper.is_none() {
+            return self.capital_at(n_period);
+        }
+        let capital = self.capital_at(n_period);
+        let state = self.simulate(n_period);
+        capital - self.tax_on(capital - state.basis, n_period, state.allowance_used)
+    }
+
+    /// Return the first period at which the withdrawals deplete the capital
+    ///
+    /// # Arguments
+    /// * `max_period` - the last period to look at
+    pub fn depletion_period(&self, max_period: u32) -> Option<u32> {
+        let start = self.withdrawals.as_ref()?.start;
+        (start..=max_period).find(|&period| self.simulate(period).capital < DEPLETED)
+    }
+
+    /// Return the fixed withdrawal by period that depletes the capital at the end of a horizon
+    ///
+    /// The capital grows at the yield rate, along the compounding and the yield curve, and gets
+    /// the contributions of the plan. The withdrawal plan, the fees, the tax on the withdrawals
+    /// and the assets of a portfolio are left out, so the amount is before fees and tax.
+    ///
+    /// # Arguments
+    /// * `start` - the first period of withdrawal
+    /// * `horizon` - the number of periods of withdrawal
+    ///
+    /// # Example
+    /// ```
+    /// // How much can be taken every month during 25 years from the 11th year
+    /// let sustainable = invest.sustainable_withdrawal(121, 300)?;
+    /// ```
+    pub fn sustainable_withdrawal(&self, start: u32, horizon: u32) -> Result<f64, HomeCalcError> {
+        check_duration("withdrawal start", start)?;
+        check_duration("withdrawal horizon", horizon)?;
+        let end = start
+            .checked_add(horizon - 1)
+            .ok_or(HomeCalcError::InvalidDuration {
+                name: "withdrawal horizon",
+                value: horizon,
+            })?;
+        let capital_with = |amount: f64| {
+            (1..=end).fold(self.capital as f64, |capital, period| {
+                let growth = 1_f64 + self.period_rate_at(period);
+                let capital = match self.timing {
+                    Timing::End => capital * growth + self.contribution_at(period),
+                    Timing::Start => (capital + self.contribution_at(period)) * growth,
+                };
+                if period >= start {
+                    capital - amount
+                } else {
+                    capital
+                }
+            })
+        };
+        let without = capital_with(0_f64);
+        if without <= 0_f64 {
+            return Err(HomeCalcError::NoSolution("sustainable withdrawal"));
+        }
+        bisect(capital_with, 0_f64, without + 1_f64, 1e-6)
+            .ok_or(HomeCalcError::NoSolution("sustainable withdrawal"))
+    }
+
+    /// Return the smallest regular addition by period reaching a target capital at a period
+    ///
+    /// The contribution plan applies to the regular addition found.
+    ///
+    /// # Arguments
+    /// * `target` - the capital to reach
+    /// * `n_period` - number of period
+    ///
+    /// # Example
+    /// ```
+    /// // How much to save every month to have 50k for a down payment in 5 years
+    /// let addition = Investment::new(10_000, 12, 0.03, 0)?.required_addition(50_000_f64, 60)?;
+    /// ```
+    pub fn required_addition(&self, target: f64, n_period: u32) -> Result<u32, HomeCalcError> {
+        check_duration("periods", n_period)?;
+        let reaches = |addition: u32| {
+            Investment {
+                regular_addition: addition,
+                ..self.clone()
+            }.capital_at(n_period)
+                >= target
+        };
+        if reaches(0) {
+            return Ok(0);
+        }
+        let mut high = 1_u32;
+        while !reaches(high) {
+            high = high
+                .checked_mul(2)
+                .ok_or(HomeCalcError::NoSolution("required addition"))?;
+        }
+        let mut low = high / 2;
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if reaches(middle) {
+                high = middle;
+            } else {
+                low = middle;
+            }
+        }
+        Ok(high)
+    }
+
+    /// Return the first period at which the capital reaches a target
+    ///
+    /// # Arguments
+    /// * `target` - the capital to reach
+    /// * `max_period` - the last period to look at
+    pub fn periods_to_reach(&self, target: f64, max_period: u32) -> Result<u32, HomeCalcError> {
+        (0..=max_period)
+            .find(|&period| self.capital_at(period) >= target)
+            .ok_or(HomeCalcError::NoSolution("periods to reach the target"))
+    }
+
+    /// Return the yield rate by year reaching a target capital at a period
+    ///
+    /// The yields of a portfolio and the later phases of a yield curve are not changed, the
+    /// result is then meaningless.
+    ///
+    /// # Arguments
+    /// * `target` - the capital to reach
+    /// * `n_period` - number of period
+    pub fn required_yield(&self, target: f64, n_period: u32) -> Result<f32, HomeCalcError> {
+        check_duration("periods", n_period)?;
+        let gap = |yield_rate: f64| {
+            Investment {
+                yield_rate: yield_rate as f32,
+                ..self.clone()
+            }.capital_at(n_period)
+                - target
+        };
+        bisect(gap, -0.99, 1_f64, 1e-7)
+            .map(|yield_rate| yield_rate as f32)
+            .ok_or(HomeCalcError::NoSolution("required yield"))
     }
 
     /// total of regular additions for a number of periods
     ///
     /// # Arguments
     /// * `n_period` - number of period
-    pub fn additions_total(&self, n_period: u32) -> u32 {
-        self.regular_addition * n_period
+    pub fn additions_total(&self, n_period: u32) -> f64 {
+        if self.plan.is_constant() {
+            return self.regular_addition as f64 * n_period as f64;
+        }
+        (1..=n_period).fold(0_f64, |total, period| total + self.contribution_at(period))
+    }
+}
+
+/// Return holdings scaled so that their total goes from an amount to another
+fn scale(holdings: Vec<f64>, to: f64, from: f64) -> Vec<f64> {
+    if from <= 0_f64 || to == from {
+        return holdings;
     }
+    holdings.into_iter().map(|holding| holding * to / from).collect()
 }
 
 #[cfg(test)]
@@ -79,12 +776,17 @@ mod tests {
     extern crate float_cmp;
 
     use super::*;
+    use cash_flow::irr;
     use investment::tests::float_cmp::ApproxEq;
+    use fees::Fees;
+    use portfolio::{Asset, Portfolio, Rebalancing};
+    use tax::{ASSURANCE_VIE_ALLOWANCE, ASSURANCE_VIE_INCOME_TAX, SOCIAL_CHARGES};
+    use withdrawal::Withdrawal;
 
     #[test]
     fn test_yield_rate_period() {
         let expected = 0.005_f32;
-        let invest = Investment::new(10000, 12, 0.06, 0);
+        let invest = Investment::new(10000, 12, 0.06, 0).unwrap();
         assert!(
             &expected.approx_eq(&invest.yield_rate_period(), 2.0 * ::std::f32::EPSILON, 2),
             "expected: {}, actual: {}",
@@ -96,7 +798,7 @@ mod tests {
     #[test]
     fn test_capital_at() {
         let expected = 43951.93_f64;
-        let invest = Investment::new(25000, 12, 0.04, 150);
+        let invest = Investment::new(25000, 12, 0.04, 150).unwrap();
         assert!(
             &expected.approx_eq(&invest.capital_at(72), 2.0 * ::std::f64::EPSILON, 200000000),
             "expected: {}, actual: {}",
@@ -105,9 +807,315 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zero_yield() {
+        let invest = Investment::new(1000, 12, 0_f32, 100).unwrap();
+        assert_eq!(2200_f64, invest.capital_at(12));
+    }
+
+    #[test]
+    fn test_invalid_investment() {
+        assert!(Investment::new(1000, 0, 0.04, 100).is_err());
+        assert!(Investment::new(1000, 12, -1_f32, 100).is_err());
+        assert!(Investment::new(1000, 12, -0.02, 100).is_ok());
+    }
+
     #[test]
     fn test_total_additions() {
-        let invest = Investment::new(25000, 12, 0.04, 234);
-        assert_eq!(19656, invest.additions_total(84));
+        let invest = Investment::new(25000, 12, 0.04, 234).unwrap();
+        assert_eq!(19656_f64, invest.additions_total(84));
+    }
+
+    #[test]
+    fn test_contribution_plan() {
+        let constant = Investment::new(25000, 12, 0.04, 150).unwrap();
+        let planned = Investment::new(25000, 12, 0.04, 150)
+            .unwrap()
+            .with_plan(ContributionPlan::default().with_change(1, 150));
+        assert!((constant.capital_at(72) - planned.capital_at(72)).abs() < 1e-6);
+
+        let plan = ContributionPlan::new(0.1)
+            .unwrap()
+            .with_pause(1, 6)
+            .with_lump_sum(12, 1000);
+        let invest = Investment::new(0, 12, 0_f32, 100).unwrap().with_plan(plan);
+        assert_eq!(1600_f64, invest.capital_at(12));
+        assert!((invest.additions_total(24) - 2920_f64).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_withdrawals() {
+        let fixed = WithdrawalPlan::new(1, Withdrawal::Fixed(1000)).unwrap();
+        let invest = Investment::new(10_000, 12, 0_f32, 0)
+            .unwrap()
+            .with_withdrawals(fixed);
+        assert_eq!(5000_f64, invest.capital_at(5));
+        assert_eq!(1000_f64, invest.withdrawal_at(10));
+        assert_eq!(0_f64, invest.withdrawal_at(11));
+        assert_eq!(10_000_f64, invest.withdrawals_total(20));
+        assert_eq!(Some(10), invest.depletion_period(20));
+
+        let percentage = WithdrawalPlan::new(13, Withdrawal::Percentage(0.04)).unwrap();
+        let invest = Investment::new(100_000, 12, 0.04, 0)
+            .unwrap()
+            .with_withdrawals(percentage);
+        assert_eq!(None, invest.depletion_period(600));
+        assert!(invest.capital_at(24) > 100_000_f64);
+    }
+
+    #[test]
+    fn test_sustainable_withdrawal() {
+        let invest = Investment::new(10_000, 12, 0_f32, 0).unwrap();
+        let sustainable = invest.sustainable_withdrawal(1, 10).unwrap();
+        assert!((sustainable - 1000_f64).abs() < 1e-4);
+
+        // The sustainable withdrawal of a capital is the term price of a loan of this capital
+        let invest = Investment::new(100_000, 12, 0.03, 0).unwrap();
+        let sustainable = invest.sustainable_withdrawal(1, 240).unwrap();
+        assert!((sustainable - 554.60).abs() < 0.01);
+        assert!(invest.sustainable_withdrawal(121, u32::MAX).is_err());
+        let withdrawals = WithdrawalPlan::new(1, Withdrawal::Fixed(555)).unwrap();
+        assert_eq!(Some(240), invest.with_withdrawals(withdrawals).depletion_period(300));
+    }
+
+    #[test]
+    fn test_cash_flows() {
+        let invest = Investment::new(1000, 12, 0.06, 100).unwrap();
+        let flows = invest.cash_flows(24);
+        assert_eq!(25, flows.len());
+        assert_eq!(-1000_f64, flows[0]);
+        assert_eq!(-100_f64, flows[1]);
+        assert!((flows[24] - (invest.capital_at(24) - 100_f64)).abs() < 1e-6);
+        let rate = irr(&flows).unwrap();
+        assert!((rate - 0.005).abs() < 1e-8);
+
+        // Taxes lower the return
+        let taxed = invest.with_wrapper(Wrapper::Taxable);
+        assert!(irr(&taxed.cash_flows(24)).unwrap() < rate);
+    }
+
+    #[test]
+    fn test_capital_with_additions() {
+        let invest = Investment::new(1000, 12, 0.06, 100).unwrap();
+        assert!((invest.capital_with_additions(&[0_f64; 24]) - invest.capital_at(24)).abs() < 1e-6);
+        let with_lump_sum = invest
+            .clone()
+            .with_plan(ContributionPlan::default().with_lump_sum(12, 500));
+        let mut additions = vec![0_f64; 24];
+        additions[11] = 500_f64;
+        assert!(
+            (invest.capital_with_additions(&additions) - with_lump_sum.capital_at(24)).abs() < 1e-6
+        );
+    }
+
+    #[test]
+    fn test_goals() {
+        let invest = Investment::new(10_000, 12, 0.06, 0).unwrap();
+        let addition = invest.required_addition(50_000_f64, 60).unwrap();
+        let with = |addition| Investment::new(10_000, 12, 0.06, addition).unwrap();
+        assert!(with(addition).capital_at(60) >= 50_000_f64);
+        assert!(with(addition - 1).capital_at(60) < 50_000_f64);
+        assert_eq!(524, addition);
+        assert_eq!(0, invest.required_addition(10_000_f64, 60).unwrap());
+
+        let saving = with(500);
+        let periods = saving.periods_to_reach(50_000_f64, 120).unwrap();
+        assert!(saving.capital_at(periods) >= 50_000_f64);
+        assert!(saving.capital_at(periods - 1) < 50_000_f64);
+        assert!(saving.periods_to_reach(50_000_f64, 12).is_err());
+
+        let target = saving.capital_at(60);
+        let yield_rate = Investment::new(10_000, 12, 0_f32, 500)
+            .unwrap()
+            .required_yield(target, 60)
+            .unwrap();
+        assert!((yield_rate - 0.06).abs() < 1e-5);
+        assert!(saving.required_yield(-1_f64, 60).is_err());
+    }
+
+    #[test]
+    fn test_taxes() {
+        let gross = Investment::new(10_000, 1, 0.125, 0).unwrap();
+        assert_eq!(gross.capital_at(2), gross.after_tax_capital_at(2));
+        let pea = Investment::new(10_000, 1, 0.125, 0)
+            .unwrap()
+            .with_wrapper(Wrapper::Pea);
+        assert!((pea.after_tax_capital_at(2) - (12656.25 - 2656.25 * 0.3)).abs() < 1e-6);
+        let gain = pea.capital_at(5) - 10_000_f64;
+        let expected = pea.capital_at(5) - gain * SOCIAL_CHARGES;
+        assert!((pea.after_tax_capital_at(5) - expected).abs() < 1e-6);
+
+        // Half of the capital is withdrawn, so half of the gain is taxed
+        let withdrawals = WithdrawalPlan::new(1, Withdrawal::Fixed(11_250)).unwrap();
+        let taxable = Investment::new(20_000, 1, 0.125, 0)
+            .unwrap()
+            .with_wrapper(Wrapper::Taxable)
+            .with_withdrawals(withdrawals);
+        assert!((taxable.taxes_total(1) - 1250_f64 * 0.3).abs() < 1e-6);
+        assert!((taxable.after_tax_capital_at(1) - (11_250_f64 - 1250_f64 * 0.3)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_assurance_vie_allowance() {
+        // Monthly withdrawals after 8 years share the allowance of each year
+        let withdrawals = WithdrawalPlan::new(97, Withdrawal::Fixed(3000)).unwrap();
+        let invest = Investment::new(100_000, 12, 0.04, 0)
+            .unwrap()
+            .with_withdrawals(withdrawals);
+        let taxable = invest.clone().with_wrapper(Wrapper::Taxable);
+        let gain = (taxable.taxes_total(108) - taxable.taxes_total(96)) / 0.3;
+        assert!(gain > ASSURANCE_VIE_ALLOWANCE);
+        let assurance_vie = invest.with_wrapper(Wrapper::AssuranceVie { couple: false });
+        let expected = gain * SOCIAL_CHARGES
+            + (gain - ASSURANCE_VIE_ALLOWANCE) * ASSURANCE_VIE_INCOME_TAX;
+        let tax = assurance_vie.taxes_total(108) - assurance_vie.taxes_total(96);
+        assert!((tax - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fees() {
+        let fees = Fees::new(0.02, 0.012).unwrap();
+        let invest = Investment::new(10_000, 12, 0_f32, 100)
+            .unwrap()
+            .with_fees(fees);
+        // 2 of entry fees then 10 of management fees on the balance
+        assert!((invest.capital_at(1) - (10_000_f64 - 10_f64 + 98_f64)).abs() < 1e-4);
+        assert!((invest.fees_total(1) - 12_f64).abs() < 1e-4);
+        assert_eq!(11_200_f64, invest.gross_capital_at(12));
+        let gap = invest.gross_capital_at(120) - invest.capital_at(120);
+        assert!((gap - invest.fees_total(120)).abs() < 1e-6);
+
+        // Management fees compound, the gap grows faster than the fees on a growing balance
+        let growing = Investment::new(10_000, 12, 0.06, 100)
+            .unwrap()
+            .with_fees(fees);
+        let gap = growing.gross_capital_at(240) - growing.capital_at(240);
+        assert!(gap > growing.fees_total(240));
+    }
+
+    #[test]
+    fn test_capital_path() {
+        let invest = Investment::new(1000, 12, 0.06, 100).unwrap();
+        let path = invest.capital_path(&[(0.06_f32 / 12_f32) as f64; 24]);
+        assert_eq!(25, path.len());
+        assert_eq!(1000_f64, path[0]);
+        assert!((path[24] - invest.capital_at(24)).abs() < 1e-6);
+        let path = invest.capital_path(&[0.1, -0.5]);
+        assert!((path[2] - ((1000_f64 * 1.1 + 100_f64) * 0.5 + 100_f64)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_schedule() {
+        let invest = Investment::new(10_000, 12, 0.06, 200)
+            .unwrap()
+            .with_fees(Fees::new(0.01, 0.005).unwrap())
+            .with_withdrawals(WithdrawalPlan::new(61, Withdrawal::Fixed(300)).unwrap())
+            .with_wrapper(Wrapper::Taxable);
+        let rows: Vec<ScheduleRow> = invest.schedule().take(120).collect();
+        assert_eq!(120, rows.len());
+        assert_eq!(10_000_f64, rows[0].opening);
+        for row in &rows {
+            let closing = row.opening + row.contribution + row.growth - row.fees - row.withdrawal;
+            assert!((row.closing - closing).abs() < 1e-9);
+        }
+        for window in rows.windows(2) {
+            assert_eq!(window[0].closing, window[1].opening);
+        }
+        let last = &rows[119];
+        assert!((last.closing - invest.capital_at(120)).abs() < 1e-6);
+        assert!((last.after_tax - invest.after_tax_capital_at(120)).abs() < 1e-6);
+        let taxes: f64 = rows.iter().map(|row| row.tax).sum();
+        assert!((taxes - invest.taxes_total(120)).abs() < 1e-6);
+        let fees: f64 = rows.iter().map(|row| row.fees).sum();
+        assert!((fees - invest.fees_total(120)).abs() < 1e-6);
+        assert_eq!(0_f64, rows[59].withdrawal);
+        assert_eq!(300_f64, rows[60].withdrawal);
+
+        // Without fees nor withdrawals the schedule follows the closed form
+        let simple = Investment::new(10_000, 12, 0.04, 100).unwrap();
+        let last = simple.schedule().nth(239).unwrap();
+        assert!((last.closing - simple.capital_at(240)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_yield_curve() {
+        let invest = Investment::new(10_000, 12, 0.06, 200)
+            .unwrap()
+            .with_curve(YieldCurve::phases(vec![(121, 0.03)]).unwrap());
+        let first = Investment::new(10_000, 12, 0.06, 200).unwrap().capital_at(120);
+        let second = Investment::new(0, 12, 0.03, 200).unwrap().capital_at(120);
+        let expected = first * (1_f64 + 0.03 / 12_f64).powi(120) + second;
+        assert!((invest.capital_at(240) - expected).abs() < 1e-2);
+        assert!((invest.capital_at(120) - first).abs() < 1e-6);
+        let last = invest.schedule().nth(239).unwrap();
+        assert!((last.closing - invest.capital_at(240)).abs() < 1e-6);
+
+        // A glide path ending before the start is the final yield all along
+        let glide = Investment::new(10_000, 12, 0.06, 200)
+            .unwrap()
+            .with_curve(YieldCurve::glide_path(70, 50, 65, 0.03).unwrap());
+        let flat = Investment::new(10_000, 12, 0.03, 200).unwrap();
+        assert!((glide.capital_at(120) - flat.capital_at(120)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_portfolio() {
+        let portfolio = |rebalancing| {
+            Portfolio::new(
+                vec![
+                    Asset::new("stocks", 0.125, 0.16, 0.5).unwrap(),
+                    Asset::new("bonds", 0.0625, 0.05, 0.5).unwrap(),
+                ],
+                rebalancing,
+            ).unwrap()
+        };
+        let drifting = Investment::new(1000, 12, 0.04, 0)
+            .unwrap()
+            .with_portfolio(portfolio(Rebalancing::Never));
+        let expected = 500_f64 * (1_f64 + 0.125 / 12_f64).powi(24)
+            + 500_f64 * (1_f64 + 0.0625 / 12_f64).powi(24);
+        assert!((drifting.capital_at(24) - expected).abs() < 1e-9);
+
+        // Rebalanced every period, the portfolio yields its average rate
+        let rebalanced = Investment::new(1000, 12, 0.04, 100)
+            .unwrap()
+            .with_portfolio(portfolio(Rebalancing::Periodic(1)));
+        let average = Investment::new(1000, 12, 0.09375, 100).unwrap();
+        assert!((rebalanced.capital_at(24) - average.capital_at(24)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_timing_and_compounding() {
+        // Future value of 100 by month at 12% a year: 1268.25 (ordinary), 1280.93 (due)
+        let ordinary = Investment::new(0, 12, 0.12, 100).unwrap();
+        let due = ordinary.clone().with_timing(Timing::Start);
+        assert!((ordinary.capital_at(12) - 1268.25).abs() < 5e-3);
+        assert!((due.capital_at(12) - 1280.93).abs() < 5e-3);
+        // A plan forces the period by period computation
+        let planned = due
+            .clone()
+            .with_plan(ContributionPlan::default().with_lump_sum(100, 0));
+        assert!((planned.capital_at(12) - due.capital_at(12)).abs() < 1e-6);
+
+        // 1000 at 5% for 10 years: 1628.89 compounded yearly, 1648.72 continuously
+        let yearly = Investment::new(1000, 12, 0.05, 0)
+            .unwrap()
+            .with_compounding(Compounding::Periodic(1));
+        let continuous = yearly.clone().with_compounding(Compounding::Continuous);
+        assert!((yearly.capital_at(120) - 1628.89).abs() < 5e-3);
+        assert!((continuous.capital_at(120) - 1648.72).abs() < 5e-3);
+        let fees = Fees::new(0_f32, 0_f32).unwrap();
+        assert!((yearly.clone().with_fees(fees).capital_at(120) - 1628.89).abs() < 5e-3);
+
+        // A Livret A at 3% fed with 100 at the start of each month
+        let livret_a = Investment::new(1000, 12, 0.03, 100)
+            .unwrap()
+            .with_timing(Timing::Start)
+            .with_compounding(Compounding::Periodic(1));
+        let simulated = livret_a
+            .clone()
+            .with_plan(ContributionPlan::default().with_lump_sum(1000, 0));
+        assert!((livret_a.capital_at(12) - simulated.capital_at(12)).abs() < 1e-6);
+        assert!((livret_a.capital_at(12) - 2249.41).abs() < 5e-3);
     }
 }
@@ -0,0 +1,421 @@
+use csv_file::{invalid_data, records};
+use date::Date;
+use error::HomeCalcError;
+use loan::{annuity, Loan};
+use std::fs;
+use std::path::Path;
+
+/// Amounts closer than this are considered equal when matching payments
+const AMOUNT_TOLERANCE: f64 = 0.01;
+
+/// An actual payment made for the loan
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payment {
+    pub date: Date,
+    pub amount: f64,
+}
+
+/// The status of an installment once compared to actual payments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstallmentStatus {
+    /// Paid as planned
+    Paid,
+    /// Nothing was paid
+    Missed,
+    /// Less than the term price was paid
+    Partial,
+    /// More than the term price was paid, the excess repays capital early
+    Prepaid,
+}
+
+/// An installment of the loan reconciled with actual payments
+#[derive(Debug, Clone)]
+pub struct InstallmentRecord {
+    pub number: u32,
+    pub due: Date,
+    pub expected: f64,
+    pub paid: f64,
+    pub payments: usize,
+    pub status: InstallmentStatus,
+    pub planned_balance: f64,
+    pub actual_balance: f64,
+}
+
+impl InstallmentRecord {
+    /// Return the amount paid in excess of the term price
+    pub fn prepayment(&self) -> f64 {
+        if self.status == InstallmentStatus::Prepaid {
+            self.paid - self.expected
+        } else {
+            0_f64
+        }
+    }
+}
+
+/// A projected installment for the remaining loan schedule
+#[derive(Debug, Clone)]
+pub struct ProjectedInstallment {
+    pub number: u32,
+    pub due: Date,
+    pub term_price: f64,
+    pub interest: f64,
+    pub capital: f64,
+    pub balance: f64,
+}
+
+/// A payment ledger tracking actual payments against a loan
+pub struct Ledger {
+    pub loan: Loan,
+    pub first_due: Date,
+    pub payments: Vec<Payment>,
+}
+
+impl Ledger {
+    /// Returns a ledger given a loan and its actual payments
+    ///
+    /// # Arguments
+    /// * `loan` - the theoretical loan
+    /// * `first_due` - due date of the first installment
+    /// * `payments` - actual payments, in any order
+    pub fn new(loan: Loan, first_due: Date, mut payments: Vec<Payment>) -> Ledger {
+        payments.sort_by_key(|payment| payment.date);
+        Ledger {
+            loan,
+            first_due,
+            payments,
+        }
+    }
+
+    /// Return the due date of an installment
+    ///
+    /// # Arguments
+    /// * `number` - the installment number, starting at 1
+    pub fn due_date(&self, number: u32) -> Date {
+        let period = self.loan.period as u32;
+        if 12 % period == 0 {
+            self.first_due.add_months((number - 1) * 12 / period)
+        } else {
+            self.first_due
+                .add_days(((number - 1) as f64 * 365.25 / period as f64).round() as i64)
+        }
+    }
+
+    /// Return the installment a payment is matched to, the last one due on or before its date
+    ///
+    /// A payment made before the first due date is matched to the first installment.
+    fn matching_installment(&self, date: &Date) -> u32 {
+        let mut number = 1;
+        while number < self.loan.periods() && self.due_date(number + 1) <= *date {
+            number += 1;
+        }
+        number
+    }
+
+    /// Return every installment due up to a date reconciled with the actual payments
+    ///
+    /// A payment is matched to the last installment due before it. What it pays over the term
+    /// price covers the next installment when its own payments fall short, e.g. for a payment
+    /// made a few days before the due date. The surplus left is a prepayment.
+    ///
+    /// # Arguments
+    /// * `as_of` - installments due after this date are not reconciled
+    pub fn reconcile(&self, as_of: &Date) -> Result<Vec<InstallmentRecord>, HomeCalcError> {
+        let mut paid_by_installment = Vec::new();
+        for payment in self.payments.iter().filter(|p| p.date <= *as_of) {
+            let number = self.matching_installment(&payment.date) as usize;
+            if paid_by_installment.len() < number {
+                paid_by_installment.resize(number, (0_f64, 0));
+            }
+            paid_by_installment[number - 1].0 += payment.amount;
+            paid_by_installment[number - 1].1 += 1;
+        }
+
+        let own_payments = |number: u32| {
+            paid_by_installment
+                .get(number as usize - 1)
+                .cloned()
+                .unwrap_or((0_f64, 0))
+        };
+        let expected = self.loan.term_price();
+        let mut actual_balance = self.loan.capital as f64;
+        let mut records = Vec::new();
+        let mut carried = 0_f64;
+        let mut number = 1;
+        while number <= self.loan.periods() && self.due_date(number) <= *as_of {
+            let (own, payments) = own_payments(number);
+            let mut paid = own + carried;
+            carried = 0_f64;
+            if number < self.loan.periods() && self.due_date(number + 1) <= *as_of {
+                let shortfall = (expected - own_payments(number + 1).0).max(0_f64);
+                carried = (paid - expected).max(0_f64).min(shortfall);
+                paid -= carried;
+            }
+            let status = if paid < AMOUNT_TOLERANCE {
+                InstallmentStatus::Missed
+            } else if paid < expected - AMOUNT_TOLERANCE {
+                InstallmentStatus::Partial
+            } else if paid > expected + AMOUNT_TOLERANCE {
+                InstallmentStatus::Prepaid
+            } else {
+                InstallmentStatus::Paid
+            };
+            actual_balance = actual_balance * (1_f64 + self.loan.interest_rate_term()) - paid;
+            records.push(InstallmentRecord {
+                number,
+                due: self.due_date(number),
+                expected,
+                paid,
+                payments,
+                status,
+                planned_balance: self.loan.balance_at(number)?,
+                actual_balance: (actual_balance * 100_f64).round() / 100_f64,
+            });
+            number += 1;
+        }
+        Ok(records)
+    }
+
+    /// Return the remaining schedule recomputed from the actual balance
+    ///
+    /// The remaining installments keep the original end date of the loan, their term price is
+    /// recomputed so that the actual balance is fully repaid.
+    ///
+    /// # Arguments
+    /// * `as_of` - the date of the last reconciled installment
+    pub fn projection(&self, as_of: &Date) -> Result<Vec<ProjectedInstallment>, HomeCalcError> {
+        let records = self.reconcile(as_of)?;
+        let (last, mut balance) = match records.last() {
+            Some(record) => (record.number, record.actual_balance),
+            None => (0, self.loan.capital as f64),
+        };
+        let remaining = self.loan.periods() - last;
+        if remaining == 0 || balance <= 0_f64 {
+            return Ok(Vec::new());
+        }
+        let rate_term = self.loan.interest_rate_term();
+        let term_price = annuity(balance, rate_term, remaining);
+        let mut projection = Vec::new();
+        for number in (last + 1)..=self.loan.periods() {
+            let interest = (balance * rate_term * 100_f64).round() / 100_f64;
+            let term_price = if number == self.loan.periods() {
+                balance + interest
+            } else {
+                term_price
+            };
+            balance -= term_price - interest;
+            projection.push(ProjectedInstallment {
+                number,
+                due: self.due_date(number),
+                term_price,
+                interest,
+                capital: term_price - interest,
+                balance: (balance * 100_f64).round() / 100_f64,
+            });
+        }
+        Ok(projection)
+    }
+}
+
+/// Load payments from a bank export, OFX when the extension is `ofx` or `qfx`, CSV otherwise
+///
+/// # Arguments
+/// * `path` - path of the bank export
+pub fn load_payments(path: &Path) -> Result<Vec<Payment>, HomeCalcError> {
+    let content = fs::read_to_string(path)?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        "ofx" | "qfx" => parse_ofx_payments(&content),
+        _ => parse_csv_payments(&content),
+    }
+}
+
+/// Parse payments from CSV lines of `date;amount` or `date,amount`
+///
+/// A header line is skipped, debits (negative amounts) are read as payments.
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_csv_payments(content: &str) -> Result<Vec<Payment>, HomeCalcError> {
+    let mut payments = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let amount = record
+            .number(1)
+            .ok_or_else(|| record.error("invalid amount"))?;
+        if amount >= 0_f64 {
+            continue;
+        }
+        payments.push(Payment {
+            date,
+            amount: amount.abs(),
+        });
+    }
+    Ok(payments)
+}
+
+/// Parse payments from the transactions of an OFX statement
+///
+/// Debits (negative amounts) are read as payments, credits are skipped.
+///
+/// # Arguments
+/// * `content` - the OFX content
+pub fn parse_ofx_payments(content: &str) -> Result<Vec<Payment>, HomeCalcError> {
+    let mut payments = Vec::new();
+    for (index, transaction) in content.split("<STMTTRN>").skip(1).enumerate() {
+        let date = ofx_value(transaction, "DTPOSTED")
+            .and_then(Date::parse)
+            .ok_or_else(|| invalid_data(index + 1, "invalid DTPOSTED"))?;
+        let amount = ofx_value(transaction, "TRNAMT")
+            .and_then(|amount| amount.replace(',', ".").parse::<f64>().ok())
+            .ok_or_else(|| invalid_data(index + 1, "invalid TRNAMT"))?;
+        if amount >= 0_f64 {
+            continue;
+        }
+        payments.push(Payment {
+            date,
+            amount: amount.abs(),
+        });
+    }
+    Ok(payments)
+}
+
+/// Return the value of an OFX tag, closing tags are optional in OFX
+fn ofx_value<'a>(content: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let start = content.find(&open)? + open.len();
+    let value = &content[start..];
+    let end = value.find(['<', '\n']).unwrap_or(value.len());
+    Some(value[..end].trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use date::date;
+
+    fn payment(year: i32, month: u32, day: u32, amount: f64) -> Payment {
+        Payment {
+            date: date(year, month, day),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv_payments() {
+        let content = "date;montant\n05/01/2018;-494,64\n2018-02-06;-494,64\n";
+        let payments = parse_csv_payments(content).unwrap();
+        assert_eq!(
+            vec![payment(2018, 1, 5, 494.64), payment(2018, 2, 6, 494.64)],
+            payments
+        );
+        assert!(parse_csv_payments("date,amount\n2018-01-05,abc").is_err());
+
+        // The credits of a bank export are not payments
+        let content = "date;amount\n2018-01-05;-494,64\n2018-01-28;2500\n2018-02-05;-494,64\n";
+        let payments = parse_csv_payments(content).unwrap();
+        assert_eq!(
+            vec![payment(2018, 1, 5, 494.64), payment(2018, 2, 5, 494.64)],
+            payments
+        );
+    }
+
+    #[test]
+    fn test_parse_ofx_payments() {
+        let content = "<OFX><BANKTRANLIST>\
+                       <STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20180105<TRNAMT>-494.64</STMTTRN>\n\
+                       <STMTTRN>\n<TRNTYPE>DEBIT\n<DTPOSTED>20180205120000\n\
+                       <TRNAMT>-1000.00\n</STMTTRN>\
+                       </BANKTRANLIST></OFX>";
+        let payments = parse_ofx_payments(content).unwrap();
+        assert_eq!(
+            vec![payment(2018, 1, 5, 494.64), payment(2018, 2, 5, 1000_f64)],
+            payments
+        );
+
+        let mixed = "<OFX><BANKTRANLIST>\
+                     <STMTTRN><TRNTYPE>CREDIT<DTPOSTED>20180128<TRNAMT>2500.00</STMTTRN>\
+                     <STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20180205<TRNAMT>-494.64</STMTTRN>\
+                     </BANKTRANLIST></OFX>";
+        let payments = parse_ofx_payments(mixed).unwrap();
+        assert_eq!(vec![payment(2018, 2, 5, 494.64)], payments);
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        let payments = vec![
+            payment(2018, 1, 4, 494.64),
+            payment(2018, 3, 5, 494.64),
+            payment(2018, 4, 6, 200_f64),
+            payment(2018, 5, 5, 494.64),
+            payment(2018, 5, 20, 5000_f64),
+        ];
+        let ledger = Ledger::new(loan, date(2018, 1, 5), payments);
+        let records = ledger.reconcile(&date(2018, 5, 31)).unwrap();
+        let statuses: Vec<InstallmentStatus> = records.iter().map(|r| r.status).collect();
+        assert_eq!(
+            vec![
+                InstallmentStatus::Paid,
+                InstallmentStatus::Missed,
+                InstallmentStatus::Paid,
+                InstallmentStatus::Partial,
+                InstallmentStatus::Prepaid,
+            ],
+            statuses
+        );
+        assert_eq!(2, records[4].payments);
+        assert_eq!(5000_f64, records[4].prepayment());
+        assert_eq!(ledger.loan.balance_at(1).unwrap(), records[0].actual_balance);
+        assert!(records[4].actual_balance < records[4].planned_balance);
+    }
+
+    #[test]
+    fn test_reconcile_early_payments() {
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        let payments = vec![
+            payment(2018, 1, 5, 494.64),
+            // Paid in advance after mid-month for the installment of March
+            payment(2018, 2, 20, 494.64),
+            payment(2018, 2, 28, 494.64),
+            payment(2018, 4, 5, 494.64),
+            payment(2018, 4, 30, 494.64),
+        ];
+        let ledger = Ledger::new(loan, date(2018, 1, 5), payments);
+        let records = ledger.reconcile(&date(2018, 4, 30)).unwrap();
+        let statuses: Vec<InstallmentStatus> = records.iter().map(|r| r.status).collect();
+        assert_eq!(
+            vec![
+                InstallmentStatus::Paid,
+                InstallmentStatus::Paid,
+                InstallmentStatus::Paid,
+                InstallmentStatus::Prepaid,
+            ],
+            statuses
+        );
+        // The payment made before the installment of May is due stays a prepayment of April
+        assert_eq!(date(2018, 4, 5), records.last().unwrap().due);
+    }
+
+    #[test]
+    fn test_projection() {
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        let payments = (0..12)
+            .map(|month| payment(2018, month + 1, 5, 494.64))
+            .collect();
+        let ledger = Ledger::new(loan, date(2018, 1, 5), payments);
+        let projection = ledger.projection(&date(2018, 12, 31)).unwrap();
+        assert_eq!(228, projection.len());
+        assert_eq!(13, projection[0].number);
+        assert_eq!(date(2019, 1, 5), projection[0].due);
+        assert!((projection[0].term_price - 494.64).abs() <= 0.01);
+        assert_eq!(0_f64, projection.last().unwrap().balance);
+    }
+}
@@ -1,4 +1,7 @@
+use error::{check_duration, check_periodicity, check_rate, HomeCalcError};
+
 /// A Loan
+#[derive(Debug, Clone, PartialEq)]
 pub struct Loan {
     pub years: u8,
     pub period: u8,
@@ -8,7 +11,7 @@ pub struct Loan {
 }
 
 impl Loan {
-    /// Returns a load given all its parameters
+    /// Returns a load given all its parameters, or an error describing the invalid parameter
     ///
     /// # Aguments
     ///
@@ -21,25 +24,34 @@ impl Loan {
     ///
     /// ```
     /// use load::Loan;
-    /// let loan_8y_50k = Loan::new(8, 12, 4.5, 50_000);
+    /// let loan_8y_50k = Loan::new(8, 12, 0.045, 50_000)?;
     /// ```
-    pub fn new(years: u8, period: u8, interest_rate_year: f32, capital: u32) -> Loan {
+    pub fn new(
+        years: u8,
+        period: u8,
+        interest_rate_year: f32,
+        capital: u32,
+    ) -> Result<Loan, HomeCalcError> {
+        check_duration("years", years as u32)?;
+        check_periodicity(period)?;
+        check_rate("interest rate", interest_rate_year)?;
         let term_price = Loan::compute_term_price(capital, interest_rate_year, years, period);
-        Loan {
+        Ok(Loan {
             years,
             period,
             interest_rate_year,
             capital,
             term_price,
-        }
+        })
     }
 
     fn compute_term_price(capital: u32, interest_rate_year: f32, years: u8, period: u8) -> f64 {
         let inretest_rate_term = interest_rate_year as f64 / period as f64;
-
-        let term_price = (capital as f64) * inretest_rate_term
-            / (1_f64 - (1_f64 + inretest_rate_term).powf(years as f64 * period as f64 * -1_f64));
-        (term_price * 100_f64).round() / 100_f64
+        annuity(
+            capital as f64,
+            inretest_rate_term,
+            years as u32 * period as u32,
+        )
     }
 
     /// Return the term price of the loan
@@ -47,6 +59,35 @@ impl Loan {
         self.term_price
     }
 
+    /// Return the total number of terms of the loan
+    pub fn periods(&self) -> u32 {
+        self.years as u32 * self.period as u32
+    }
+
+    /// Return the interest rate applied for one term
+    pub fn interest_rate_term(&self) -> f64 {
+        self.interest_rate_year as f64 / self.period as f64
+    }
+
+    /// Return the capital remaining due at a moment of the loan
+    ///
+    /// # Arguments
+    /// * `n_period` - number of period
+    pub fn balance_at(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        Ok(((self.capital as f64 - self.capital_at(n_period)?) * 100_f64).round() / 100_f64)
+    }
+
+    fn check_period(&self, n_period: u32) -> Result<u32, HomeCalcError> {
+        if n_period <= self.periods() {
+            Ok(n_period)
+        } else {
+            Err(HomeCalcError::PeriodOutOfRange {
+                period: n_period,
+                max: self.periods(),
+            })
+        }
+    }
+
     /// Return the capital paid at a moment of the loan
     ///
     /// # Arguments
@@ -56,13 +97,19 @@ impl Loan {
     /// ```
     /// // We have a loan with perodicity of 12 terms in a year
     /// // Get the capital paid at 2 years
-    /// let capital_at_2y = loan.capital_at(24);
+    /// let capital_at_2y = loan.capital_at(24)?;
     /// ```
-    pub fn capital_at(&self, n_period: u32) -> f64 {
+    pub fn capital_at(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        self.check_period(n_period)?;
         let inretest_rate_term = self.interest_rate_year as f64 / self.period as f64;
-        let capital_n = ((1_f64 + inretest_rate_term).powf(n_period as f64) - 1_f64)
-            / ((1_f64 + inretest_rate_term).powf(self.years as f64 * self.period as f64) - 1_f64);
-        (capital_n * self.capital as f64 * 100_f64).round() / 100_f64
+        let capital_n = if inretest_rate_term == 0_f64 {
+            n_period as f64 / self.periods() as f64
+        } else {
+            ((1_f64 + inretest_rate_term).powf(n_period as f64) - 1_f64)
+                / ((1_f64 + inretest_rate_term).powf(self.years as f64 * self.period as f64)
+                    - 1_f64)
+        };
+        Ok((capital_n * self.capital as f64 * 100_f64).round() / 100_f64)
     }
 
     /// Return the amount paid at a moment of the loan
@@ -73,10 +120,11 @@ impl Loan {
     /// # Example
     /// ```
     /// // We have a loan with perodicity of 12 terms in a year
-    /// let paid_at_1y = loan.paid_at(12);
+    /// let paid_at_1y = loan.paid(12)?;
     /// ```
-    pub fn paid(&self, n_period: u32) -> f64 {
-        (self.term_price * n_period as f64 * 100_f64).round() / 100_f64
+    pub fn paid(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        self.check_period(n_period)?;
+        Ok((self.term_price * n_period as f64 * 100_f64).round() / 100_f64)
     }
 
     /// Return the interest paid at a moment of the loan
@@ -87,45 +135,110 @@ impl Loan {
     /// # Example
     /// ```
     /// // We have a loan with perodicity of 12 terms in a year
-    /// let interest_at_4y = loan.interest_at(48);
+    /// let interest_at_4y = loan.interest_at(48)?;
+    /// ```
+    pub fn interest_at(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        Ok(((self.paid(n_period)? - self.capital_at(n_period)?) * 100_f64).round() / 100_f64)
+    }
+
+    /// Return the cash flows of the loan for the borrower: the capital received then the terms
+    ///
+    /// # Example
+    /// ```
+    /// // The internal rate of return of a loan is its interest rate by term
+    /// let rate_term = cash_flow::irr(&loan.cash_flows())?;
     /// ```
-    pub fn interest_at(&self, n_period: u32) -> f64 {
-        ((self.paid(n_period) - self.capital_at(n_period)) * 100_f64).round() / 100_f64
+    pub fn cash_flows(&self) -> Vec<f64> {
+        let mut flows = vec![-self.term_price; self.periods() as usize + 1];
+        flows[0] = self.capital as f64;
+        flows
     }
 }
 
+/// Return the term price needed to repay a capital in a number of terms
+///
+/// # Arguments
+/// * `capital` - the capital to repay
+/// * `interest_rate_term` - interest rate for one term
+/// * `n_terms` - number of terms
+pub fn annuity(capital: f64, interest_rate_term: f64, n_terms: u32) -> f64 {
+    if interest_rate_term == 0_f64 {
+        return (capital / n_terms as f64 * 100_f64).round() / 100_f64;
+    }
+    let term_price = capital * interest_rate_term
+        / (1_f64 - (1_f64 + interest_rate_term).powf(-(n_terms as f64)));
+    (term_price * 100_f64).round() / 100_f64
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cash_flow::irr;
 
     #[test]
     fn test_term_price() {
-        let loan = Loan::new(20, 12, 0.029_f32, 90_000);
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
         assert_eq!(494.64, loan.term_price());
     }
 
     #[test]
     fn test_capital_at() {
-        let loan = Loan::new(20, 12, 0.029_f32, 90_000);
-        assert_eq!(1954.13, loan.capital_at(7));
-        assert_eq!(2801.78, loan.capital_at(10));
-        assert_eq!(34874.26, loan.capital_at(110));
-        assert_eq!(72965.12, loan.capital_at(204));
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        assert_eq!(1954.13, loan.capital_at(7).unwrap());
+        assert_eq!(2801.78, loan.capital_at(10).unwrap());
+        assert_eq!(34874.26, loan.capital_at(110).unwrap());
+        assert_eq!(72965.12, loan.capital_at(204).unwrap());
     }
 
     #[test]
     fn test_paid() {
-        let loan = Loan::new(20, 12, 0.029_f32, 90_000);
-        assert_eq!(4451.76, loan.paid(9));
-        assert_eq!(15333.84, loan.paid(31));
-        assert_eq!(83099.52, loan.paid(168));
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        assert_eq!(4451.76, loan.paid(9).unwrap());
+        assert_eq!(15333.84, loan.paid(31).unwrap());
+        assert_eq!(83099.52, loan.paid(168).unwrap());
     }
 
     #[test]
     fn test_interest_at() {
-        let loan = Loan::new(20, 12, 0.029_f32, 90_000);
-        assert_eq!(2355.34, loan.interest_at(11));
-        assert_eq!(22487.93, loan.interest_at(134));
-        assert_eq!(27899.18, loan.interest_at(203));
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        assert_eq!(2355.34, loan.interest_at(11).unwrap());
+        assert_eq!(22487.93, loan.interest_at(134).unwrap());
+        assert_eq!(27899.18, loan.interest_at(203).unwrap());
+    }
+
+    #[test]
+    fn test_cash_flows() {
+        let loan = Loan::new(20, 12, 0.03, 200_000).unwrap();
+        let flows = loan.cash_flows();
+        assert_eq!(241, flows.len());
+        assert_eq!(200_000_f64, flows[0]);
+        assert_eq!(-loan.term_price(), flows[240]);
+        let rate = irr(&flows).unwrap();
+        assert!((rate - loan.interest_rate_term()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_balance_at() {
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        assert_eq!(87198.22, loan.balance_at(10).unwrap());
+        assert_eq!(0.0, loan.balance_at(loan.periods()).unwrap());
+    }
+
+    #[test]
+    fn test_zero_rate() {
+        let loan = Loan::new(10, 12, 0_f32, 12_000).unwrap();
+        assert_eq!(100_f64, loan.term_price());
+        assert_eq!(6000_f64, loan.capital_at(60).unwrap());
+        assert_eq!(0_f64, loan.interest_at(60).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_loan() {
+        assert!(Loan::new(0, 12, 0.029_f32, 90_000).is_err());
+        assert!(Loan::new(20, 0, 0.029_f32, 90_000).is_err());
+        assert!(Loan::new(20, 12, -0.01_f32, 90_000).is_err());
+        let loan = Loan::new(20, 12, 0.029_f32, 90_000).unwrap();
+        assert!(loan.capital_at(241).is_err());
+        assert!(loan.interest_at(241).is_err());
     }
 }
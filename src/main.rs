@@ -2,34 +2,94 @@ extern crate clap;
 #[macro_use]
 extern crate prettytable;
 
+/// The cash flow module
+mod cash_flow;
 /// The CLI module
 mod cli;
 /// The comparators module
 mod comparators;
+/// The compounding module
+mod compounding;
+/// The contribution module
+mod contribution;
+/// The CSV file module
+mod csv_file;
+/// The date module
+mod date;
+/// The error module
+mod error;
+/// The fees module
+mod fees;
+/// The history module
+mod history;
+/// The home price module
+mod home_price;
+/// The home savings module
+mod home_savings;
+/// The indexed loan module
+mod indexed_loan;
+/// The inflation module
+mod inflation;
 /// The investment module
 mod investment;
+/// The ledger module
+mod ledger;
 /// The loan module
 mod loan;
+/// The monte carlo module
+mod monte_carlo;
+/// The offers module
+mod offers;
+/// The owner costs module
+mod owner_costs;
+/// The portfolio module
+mod portfolio;
+/// The purchase plan module
+mod purchase_plan;
+/// The regulated module
+mod regulated;
+/// The rent module
+mod rent;
+/// The solver module
+mod solver;
+/// The stress module
+mod stress;
+/// The tax module
+mod tax;
+/// The withdrawal module
+mod withdrawal;
+/// The yield curve module
+mod yield_curve;
 
 use clap::App;
+use cli::home::{execute_home_sub_command, home_sub_commands, SUB_HOME};
+use cli::inflation::inflation_args;
 use cli::invest::{execute_invest_sub_command, invest_sub_commands, SUB_INVEST};
 use cli::loan::{execute_loan_sub_command, loan_sub_command, SUB_LOAN};
-use cli::home::{execute_home_sub_command, home_sub_commands, SUB_HOME};
+use std::process;
 
 fn main() {
     let app_matches = App::new("HomeCalc")
         .version("0.0.1")
         .author("Clément Bizeau")
         .about("Various computations about homes")
+        .args(&inflation_args())
         .subcommands(loan_sub_command())
         .subcommands(invest_sub_commands())
         .subcommands(home_sub_commands())
         .get_matches();
 
-    match app_matches.subcommand() {
+    let result = match app_matches.subcommand() {
         (SUB_LOAN, Some(loan_matches)) => execute_loan_sub_command(loan_matches),
         (SUB_INVEST, Some(invest_matches)) => execute_invest_sub_command(invest_matches),
         (SUB_HOME, Some(home_matches)) => execute_home_sub_command(home_matches),
-        _ => println!("*** No command found"),
+        _ => {
+            println!("*** No command found");
+            Ok(())
+        }
+    };
+    if let Err(error) = result {
+        eprintln!("*** Error: {}", error);
+        process::exit(1);
     }
 }
@@ -0,0 +1,420 @@
+use error::{check_duration, check_rate, HomeCalcError};
+use investment::Investment;
+use std::f64::consts::PI;
+use std::fmt;
+
+/// A seeded pseudo random generator (SplitMix64), the same seed gives the same draws
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Returns a generator given its seed
+    ///
+    /// # Arguments
+    /// * `seed` - the seed
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Return a random integer
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Return a random number uniformly drawn in [0, 1)
+    pub fn uniform(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Return a random number drawn from the standard normal distribution (Box-Muller)
+    pub fn normal(&mut self) -> f64 {
+        let u1 = 1_f64 - self.uniform();
+        let u2 = self.uniform();
+        (-2_f64 * u1.ln()).sqrt() * (2_f64 * PI * u2).cos()
+    }
+
+    /// Return a random number drawn from the Student-t distribution
+    ///
+    /// # Arguments
+    /// * `degrees` - degrees of freedom
+    pub fn student_t(&mut self, degrees: u32) -> f64 {
+        let chi_squared: f64 = (0..degrees).map(|_| self.normal().powi(2)).sum();
+        self.normal() / (chi_squared / degrees as f64).sqrt()
+    }
+}
+
+/// The distribution of the returns of an investment
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    /// Returns with a normal distribution given their yearly mean and volatility
+    Normal { mean: f32, volatility: f32 },
+    /// Returns whose logarithm has a normal distribution, given their yearly mean and volatility
+    LogNormal { mean: f32, volatility: f32 },
+    /// Returns with fat tails given their yearly mean and volatility
+    StudentT {
+        mean: f32,
+        volatility: f32,
+        degrees: u32,
+    },
+    /// Returns drawn from historical returns of the same periodicity
+    Bootstrap(Vec<f64>),
+}
+
+impl Distribution {
+    /// Check the parameters of the distribution
+    pub fn check(&self) -> Result<(), HomeCalcError> {
+        match *self {
+            Distribution::Normal { volatility, .. }
+            | Distribution::LogNormal { volatility, .. } => {
+                check_rate("volatility", volatility)?;
+            }
+            Distribution::StudentT {
+                volatility,
+                degrees,
+                ..
+            } => {
+                check_rate("volatility", volatility)?;
+                if degrees <= 2 {
+                    return Err(HomeCalcError::InvalidValue {
+                        name: "degrees of freedom (more than 2)".to_string(),
+                        value: degrees.to_string(),
+                    });
+                }
+            }
+            Distribution::Bootstrap(ref returns) => {
+                if returns.is_empty() {
+                    return Err(HomeCalcError::InvalidValue {
+                        name: "historical returns".to_string(),
+                        value: String::new(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the same kind of distribution with other yearly mean and volatility
+    ///
+    /// Historical returns are kept as they are.
+    ///
+    /// # Arguments
+    /// * `mean` - the yearly mean
+    /// * `volatility` - the yearly volatility
+    pub fn with_moments(&self, mean: f32, volatility: f32) -> Distribution {
+        match *self {
+            Distribution::Normal { .. } => Distribution::Normal { mean, volatility },
+            Distribution::LogNormal { .. } => Distribution::LogNormal { mean, volatility },
+            Distribution::StudentT { degrees, .. } => Distribution::StudentT {
+                mean,
+                volatility,
+                degrees,
+            },
+            Distribution::Bootstrap(ref returns) => Distribution::Bootstrap(returns.clone()),
+        }
+    }
+
+    /// Return the return of a period
+    ///
+    /// # Arguments
+    /// * `rng` - the random generator
+    /// * `periodicity` - number of periods by year
+    pub fn draw(&self, rng: &mut Rng, periodicity: u8) -> f64 {
+        let periodicity = periodicity as f64;
+        match *self {
+            Distribution::Normal { mean, volatility } => {
+                mean as f64 / periodicity + volatility as f64 / periodicity.sqrt() * rng.normal()
+            }
+            Distribution::LogNormal { mean, volatility } => {
+                // The mean and the standard deviation of the returns are kept
+                let growth = 1_f64 + mean as f64 / periodicity;
+                let variance = (volatility as f64).powi(2) / periodicity;
+                let sigma = (1_f64 + variance / growth.powi(2)).ln().sqrt();
+                let mu = growth.ln() - sigma * sigma / 2_f64;
+                (mu + sigma * rng.normal()).exp() - 1_f64
+            }
+            Distribution::StudentT {
+                mean,
+                volatility,
+                degrees,
+            } => {
+                // The draw is scaled so that its standard deviation is the volatility
+                let scale = ((degrees as f64 - 2_f64) / degrees as f64).sqrt();
+                mean as f64 / periodicity
+                    + volatility as f64 / periodicity.sqrt() * scale * rng.student_t(degrees)
+            }
+            Distribution::Bootstrap(ref returns) => {
+                returns[(rng.uniform() * returns.len() as f64) as usize]
+            }
+        }
+        .max(-1_f64)
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Distribution::Normal { mean, volatility } => write!(
+                f,
+                "normal (mean {}%, volatility {}%)",
+                mean * 100_f32,
+                volatility * 100_f32
+            ),
+            Distribution::LogNormal { mean, volatility } => write!(
+                f,
+                "log-normal (mean {}%, volatility {}%)",
+                mean * 100_f32,
+                volatility * 100_f32
+            ),
+            Distribution::StudentT {
+                mean,
+                volatility,
+                degrees,
+            } => write!(
+                f,
+                "Student-t (mean {}%, volatility {}%, {} degrees of freedom)",
+                mean * 100_f32,
+                volatility * 100_f32,
+                degrees
+            ),
+            Distribution::Bootstrap(ref returns) => {
+                write!(f, "bootstrap of {} historical", returns.len())
+            }
+        }
+    }
+}
+
+/// The percentiles of the capital at a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bands {
+    pub period: u32,
+    pub p5: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
+/// The capital paths of every draw of a Monte Carlo simulation
+pub struct Simulation {
+    /// The capital at the start and at the end of each period, for each draw
+    pub paths: Vec<Vec<f64>>,
+}
+
+impl Simulation {
+    /// Check that a period was simulated
+    fn check_period(&self, period: u32) -> Result<(), HomeCalcError> {
+        let max = self.paths.first().map_or(0, |path| path.len() as u32 - 1);
+        if period > max {
+            return Err(HomeCalcError::PeriodOutOfRange { period, max });
+        }
+        Ok(())
+    }
+
+    fn sorted_at(&self, period: u32) -> Result<Vec<f64>, HomeCalcError> {
+        self.check_period(period)?;
+        let mut capitals: Vec<f64> = self.paths.iter().map(|p| p[period as usize]).collect();
+        capitals.sort_by(f64::total_cmp);
+        Ok(capitals)
+    }
+
+    /// Return the percentile bands of the capital at a period, or an error for a period after
+    /// the end of the simulation
+    ///
+    /// # Arguments
+    /// * `period` - the period
+    pub fn bands(&self, period: u32) -> Result<Bands, HomeCalcError> {
+        let capitals = self.sorted_at(period)?;
+        Ok(Bands {
+            period,
+            p5: percentile(&capitals, 0.05),
+            p25: percentile(&capitals, 0.25),
+            p50: percentile(&capitals, 0.5),
+            p75: percentile(&capitals, 0.75),
+            p95: percentile(&capitals, 0.95),
+        })
+    }
+
+    /// Return the probability for the capital to reach a target at a period, or an error for a
+    /// period after the end of the simulation
+    ///
+    /// # Arguments
+    /// * `target` - the target capital
+    /// * `period` - the period
+    pub fn probability_of(&self, target: f64, period: u32) -> Result<f64, HomeCalcError> {
+        self.check_period(period)?;
+        let reached = self
+            .paths
+            .iter()
+            .filter(|path| path[period as usize] >= target)
+            .count();
+        Ok(reached as f64 / self.paths.len() as f64)
+    }
+}
+
+/// Return a percentile of sorted values, interpolated between the closest ranks
+fn percentile(sorted: &[f64], ratio: f64) -> f64 {
+    let rank = ratio * (sorted.len() - 1) as f64;
+    let low = rank.floor() as usize;
+    let high = rank.ceil() as usize;
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+/// Simulate an investment with random returns
+///
+/// The contributions, fees and withdrawals of the investment apply, its yield rate is replaced
+/// by the draws. Each asset of a portfolio is drawn independently with its own yield and
+/// volatility.
+///
+/// # Arguments
+/// * `invest` - the investment
+/// * `distribution` - the distribution of the returns
+/// * `n_period` - number of periods to simulate
+/// * `simulations` - number of draws
+/// * `seed` - the seed of the random generator
+///
+/// # Example
+/// ```
+/// let distribution = Distribution::Normal { mean: 0.06, volatility: 0.15 };
+/// let simulation = simulate(&invest, &distribution, 240, 1000, 42)?;
+/// let median_at_20y = simulation.bands(240)?.p50;
+/// ```
+pub fn simulate(
+    invest: &Investment,
+    distribution: &Distribution,
+    n_period: u32,
+    simulations: u32,
+    seed: u64,
+) -> Result<Simulation, HomeCalcError> {
+    distribution.check()?;
+    check_duration("simulations", simulations)?;
+    let distributions = match invest.portfolio {
+        Some(ref portfolio) => portfolio
+            .assets
+            .iter()
+            .map(|asset| distribution.with_moments(asset.yield_rate, asset.volatility))
+            .collect(),
+        None => vec![distribution.clone()],
+    };
+    let mut rng = Rng::new(seed);
+    let paths = (0..simulations)
+        .map(|_| {
+            let returns: Vec<Vec<f64>> = (0..n_period)
+                .map(|_| {
+                    distributions
+                        .iter()
+                        .map(|asset| asset.draw(&mut rng, invest.periodicity))
+                        .collect()
+                }).collect();
+            invest.capital_path_by_asset(&returns)
+        }).collect();
+    Ok(Simulation { paths })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use portfolio::{Asset, Portfolio, Rebalancing};
+
+    fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let variance =
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        (mean, variance.sqrt())
+    }
+
+    #[test]
+    fn test_distributions() {
+        let mut rng = Rng::new(7);
+        let distributions = vec![
+            Distribution::Normal {
+                mean: 0.06,
+                volatility: 0.12,
+            },
+            Distribution::LogNormal {
+                mean: 0.06,
+                volatility: 0.12,
+            },
+            Distribution::StudentT {
+                mean: 0.06,
+                volatility: 0.12,
+                degrees: 5,
+            },
+        ];
+        for distribution in distributions {
+            let draws: Vec<f64> = (0..100_000).map(|_| distribution.draw(&mut rng, 1)).collect();
+            let (mean, deviation) = mean_and_deviation(&draws);
+            assert!((mean - 0.06).abs() < 0.002, "{:?}: {}", distribution, mean);
+            assert!((deviation - 0.12).abs() < 0.005, "{:?}: {}", distribution, deviation);
+        }
+        let bootstrap = Distribution::Bootstrap(vec![0.01, 0.02]);
+        assert!((0..100).all(|_| {
+            let draw = bootstrap.draw(&mut rng, 12);
+            draw == 0.01 || draw == 0.02
+        }));
+        assert!(Distribution::Bootstrap(Vec::new()).check().is_err());
+        assert_eq!("bootstrap of 2 historical", bootstrap.to_string());
+        let normal = Distribution::Normal {
+            mean: 0.5,
+            volatility: 0.25,
+        };
+        assert_eq!("normal (mean 50%, volatility 25%)", normal.to_string());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let invest = Investment::new(10_000, 12, 0.05, 100).unwrap();
+        let distribution = Distribution::Normal {
+            mean: 0.05,
+            volatility: 0.15,
+        };
+        let simulation = simulate(&invest, &distribution, 120, 2000, 42).unwrap();
+        let bands = simulation.bands(120).unwrap();
+        assert!(bands.p5 < bands.p25 && bands.p25 < bands.p50);
+        assert!(bands.p50 < bands.p75 && bands.p75 < bands.p95);
+        // Volatility drags the median below the deterministic capital
+        assert!(bands.p50 < invest.capital_at(120));
+        assert!(bands.p95 > invest.capital_at(120));
+        assert_eq!(1_f64, simulation.probability_of(0_f64, 120).unwrap());
+        assert!(simulation.bands(121).is_err());
+        assert!(simulation.probability_of(0_f64, 121).is_err());
+        assert_eq!(10_000_f64, simulation.bands(0).unwrap().p50);
+
+        let again = simulate(&invest, &distribution, 120, 2000, 42).unwrap();
+        assert_eq!(bands, again.bands(120).unwrap());
+
+        // Without volatility every draw is the deterministic investment
+        let certain = Distribution::Normal {
+            mean: 0.05,
+            volatility: 0_f32,
+        };
+        let simulation = simulate(&invest, &certain, 120, 10, 1).unwrap();
+        assert!((simulation.bands(120).unwrap().p5 - invest.capital_at(120)).abs() < 1e-3);
+
+        // Each asset of a portfolio follows its own distribution
+        let portfolio = Portfolio::new(
+            vec![
+                Asset::new("stocks", 0.07, 0.16, 0.5).unwrap(),
+                Asset::new("bonds", 0.03, 0_f32, 0.5).unwrap(),
+            ],
+            Rebalancing::Never,
+        ).unwrap();
+        let spread = invest.with_portfolio(portfolio);
+        let simulation = simulate(&spread, &distribution, 120, 2000, 42).unwrap();
+        let bands = simulation.bands(120).unwrap();
+        assert!(bands.p5 > 0.5 * 10_000_f64 * (1_f64 + 0.03 / 12_f64).powi(120));
+        assert!(bands.p95 - bands.p5 > 10_000_f64);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = vec![1_f64, 2_f64, 3_f64, 4_f64, 5_f64];
+        assert_eq!(3_f64, percentile(&values, 0.5));
+        assert_eq!(1.2, percentile(&values, 0.05));
+        assert_eq!(5_f64, percentile(&values, 1_f64));
+    }
+}
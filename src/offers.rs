@@ -0,0 +1,250 @@
+use comparators::PERIODICITY;
+use csv_file::records;
+use error::{check_amount, check_rate, HomeCalcError};
+use loan::Loan;
+use solver::bisect;
+use std::fs;
+use std::path::Path;
+
+/// Early repayment penalties are capped to this many months of interest
+const PENALTY_MONTHS_OF_INTEREST: f64 = 6_f64;
+/// Early repayment penalties are capped to this ratio of the remaining capital
+const PENALTY_CAPITAL_RATIO: f64 = 0.03;
+
+/// A mortgage offer from a bank
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offer {
+    pub name: String,
+    pub loan: Loan,
+    pub insurance_rate_year: f32,
+    pub fees: f64,
+    pub guarantee: f64,
+}
+
+impl Offer {
+    /// Returns an offer given all its parameters, or an error for an invalid parameter
+    ///
+    /// # Arguments
+    /// * `name` - name of the bank or the offer
+    /// * `capital` - capital to borrow
+    /// * `years` - number of years of the loan
+    /// * `interest_rate_year` - interest rate by year of the loan
+    /// * `insurance_rate_year` - insurance rate by year on the initial capital
+    /// * `fees` - application fees
+    /// * `guarantee` - cost of the guarantee (mortgage or bond)
+    pub fn new(
+        name: &str,
+        capital: u32,
+        years: u8,
+        interest_rate_year: f32,
+        insurance_rate_year: f32,
+        fees: f64,
+        guarantee: f64,
+    ) -> Result<Offer, HomeCalcError> {
+        Ok(Offer {
+            name: name.to_string(),
+            loan: Loan::new(years, PERIODICITY, interest_rate_year, capital)?,
+            insurance_rate_year: check_rate("insurance rate", insurance_rate_year)?,
+            fees: check_amount("fees", fees)?,
+            guarantee: check_amount("guarantee", guarantee)?,
+        })
+    }
+
+    /// Return the insurance paid each month
+    pub fn insurance_term(&self) -> f64 {
+        let insurance =
+            self.loan.capital as f64 * self.insurance_rate_year as f64 / PERIODICITY as f64;
+        (insurance * 100_f64).round() / 100_f64
+    }
+
+    /// Return the monthly payment, insurance included
+    pub fn monthly_payment(&self) -> f64 {
+        self.loan.term_price() + self.insurance_term()
+    }
+
+    /// Return the total cost of the credit when kept until its end
+    pub fn total_cost(&self) -> Result<f64, HomeCalcError> {
+        self.cost_at(self.loan.periods())
+    }
+
+    /// Return the cost of the credit when repaid at some point, for instance on resale
+    ///
+    /// The cost includes interest, insurance, fees, guarantee and the early repayment penalty.
+    ///
+    /// # Arguments
+    /// * `n_period` - number of months before the repayment
+    pub fn cost_at(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        let n_period = n_period.min(self.loan.periods());
+        Ok(self.loan.interest_at(n_period)?
+            + self.insurance_term() * n_period as f64
+            + self.fees
+            + self.guarantee
+            + self.early_repayment_penalty(n_period)?)
+    }
+
+    /// Return the legal early repayment penalty: the lowest of 6 months of interest and 3% of
+    /// the remaining capital
+    ///
+    /// # Arguments
+    /// * `n_period` - number of months before the repayment
+    pub fn early_repayment_penalty(&self, n_period: u32) -> Result<f64, HomeCalcError> {
+        if n_period >= self.loan.periods() {
+            return Ok(0_f64);
+        }
+        let balance = self.loan.balance_at(n_period)?;
+        let interest = balance * self.loan.interest_rate_term() * PENALTY_MONTHS_OF_INTEREST;
+        let penalty = interest.min(balance * PENALTY_CAPITAL_RATIO);
+        Ok((penalty * 100_f64).round() / 100_f64)
+    }
+
+    /// Return the annual percentage rate (APR) of the offer
+    ///
+    /// This is the actuarial rate for which the monthly payments are worth the capital actually
+    /// received, net of fees and guarantee.
+    pub fn apr(&self) -> Result<f64, HomeCalcError> {
+        let received = self.loan.capital as f64 - self.fees - self.guarantee;
+        let payment = self.monthly_payment();
+        let periods = self.loan.periods() as f64;
+        let present_value = |rate: f64| {
+            if rate == 0_f64 {
+                payment * periods - received
+            } else {
+                payment * (1_f64 - (1_f64 + rate).powf(-periods)) / rate - received
+            }
+        };
+        bisect(present_value, 0_f64, 1_f64, 1e-12)
+            .map(|rate| (1_f64 + rate).powf(PERIODICITY as f64) - 1_f64)
+            .ok_or(HomeCalcError::NoSolution("APR"))
+    }
+}
+
+/// Sort offers from the cheapest to the most expensive
+///
+/// # Arguments
+/// * `offers` - the offers to rank
+/// * `horizon` - number of months before resale, the whole duration of each offer if `None`
+pub fn rank_offers(
+    offers: Vec<Offer>,
+    horizon: Option<u32>,
+) -> Result<Vec<Offer>, HomeCalcError> {
+    let mut ranked = offers
+        .into_iter()
+        .map(|offer| {
+            let cost = match horizon {
+                Some(horizon) => offer.cost_at(horizon),
+                None => offer.total_cost(),
+            };
+            cost.map(|cost| (cost, offer))
+        }).collect::<Result<Vec<(f64, Offer)>, HomeCalcError>>()?;
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(ranked.into_iter().map(|(_, offer)| offer).collect())
+}
+
+/// Load offers from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_offers(path: &Path) -> Result<Vec<Offer>, HomeCalcError> {
+    parse_offers(&fs::read_to_string(path)?)
+}
+
+/// Parse offers from CSV lines of
+/// `name;capital;years;rate;insurance;fees;guarantee`, rates being in percent
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_offers(content: &str) -> Result<Vec<Offer>, HomeCalcError> {
+    let mut offers = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let capital = match record.number(1) {
+            Some(capital) => capital,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid capital").into()),
+        };
+        let field = |index: usize, name: &str| {
+            record
+                .number(index)
+                .ok_or_else(|| record.error(&format!("invalid {}", name)))
+        };
+        // Whole numbers only, rather than saturating or truncating the value
+        let integral = |value: f64, max: f64, name: &str| {
+            if value < 0_f64 || value > max || value.fract() != 0_f64 {
+                return Err(record.error(&format!("invalid {}", name)));
+            }
+            Ok(value)
+        };
+        let capital = integral(capital, u32::MAX as f64, "capital")?;
+        let years = integral(field(2, "years")?, u8::MAX as f64, "years")?;
+        offers.push(Offer::new(
+            record.text(0).unwrap_or(""),
+            capital as u32,
+            years as u8,
+            (field(3, "rate")? / 100_f64) as f32,
+            (field(4, "insurance")? / 100_f64) as f32,
+            field(5, "fees")?,
+            field(6, "guarantee")?,
+        )?);
+    }
+    Ok(offers)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate float_cmp;
+
+    use self::float_cmp::ApproxEq;
+    use super::*;
+
+    fn offer() -> Offer {
+        Offer::new("bank", 200_000, 20, 0.015, 0.0036, 1000_f64, 2500_f64).unwrap()
+    }
+
+    #[test]
+    fn test_parse_offers() {
+        let content = "name;capital;years;rate;insurance;fees;guarantee\n\
+                       bank;200000;20;1,5;0,36;1000;2500\n";
+        assert_eq!(vec![offer()], parse_offers(content).unwrap());
+        assert!(parse_offers("bank;200000;20;abc;0;0;0").is_err());
+        assert!(parse_offers("bank;200000;0;1;0;0;0").is_err());
+        assert!(parse_offers("bank;-200000;20;1;0;0;0").is_err());
+        assert!(parse_offers("bank;200000;300;1;0;0;0").is_err());
+        assert!(parse_offers("bank;200000;20,5;1;0;0;0").is_err());
+    }
+
+    #[test]
+    fn test_costs() {
+        let offer = offer();
+        assert_eq!(60_f64, offer.insurance_term());
+        assert_eq!(
+            offer.loan.term_price() + 60_f64,
+            offer.monthly_payment()
+        );
+        let expected_total = offer.loan.interest_at(240).unwrap() + 60_f64 * 240_f64 + 3500_f64;
+        assert!(expected_total.approx_eq(&offer.total_cost().unwrap(), 1e-6, 2));
+        let balance_7y = offer.loan.balance_at(84).unwrap();
+        let expected_penalty = (balance_7y * 0.015 / 12_f64 * 6_f64 * 100_f64).round() / 100_f64;
+        assert_eq!(expected_penalty, offer.early_repayment_penalty(84).unwrap());
+        assert_eq!(0_f64, offer.early_repayment_penalty(240).unwrap());
+    }
+
+    #[test]
+    fn test_apr() {
+        let no_fees = Offer::new("bank", 200_000, 20, 0.015, 0_f32, 0_f64, 0_f64).unwrap();
+        let expected = (1_f64 + 0.015 / 12_f64).powf(12_f64) - 1_f64;
+        assert!((expected - no_fees.apr().unwrap()).abs() < 1e-5);
+        assert!(offer().apr().unwrap() > no_fees.apr().unwrap() + 0.004);
+    }
+
+    #[test]
+    fn test_rank_offers() {
+        let expensive =
+            Offer::new("expensive", 200_000, 20, 0.019, 0.0036, 0_f64, 0_f64).unwrap();
+        let no_fees = Offer::new("no fees", 200_000, 20, 0.018, 0.0036, 0_f64, 0_f64).unwrap();
+        let offers = rank_offers(vec![expensive, offer(), no_fees], None).unwrap();
+        let names: Vec<&str> = offers.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(vec!["bank", "no fees", "expensive"], names);
+        let offers = rank_offers(offers, Some(12)).unwrap();
+        let names: Vec<&str> = offers.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(vec!["no fees", "expensive", "bank"], names);
+    }
+}
@@ -0,0 +1,209 @@
+use error::{check_rate, HomeCalcError};
+use std::fmt;
+use std::str::FromStr;
+
+/// The costs paid by the owner of a home on top of the loan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostKind {
+    /// The property tax (taxe foncière)
+    PropertyTax,
+    /// The charges of the co-ownership
+    CoOwnership,
+    /// The home insurance
+    Insurance,
+    /// The reserve for the maintenance
+    Maintenance,
+    /// The large works, e.g. a new roof or facade
+    LargeWorks,
+}
+
+impl fmt::Display for CostKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            CostKind::PropertyTax => "property tax",
+            CostKind::CoOwnership => "co-ownership charges",
+            CostKind::Insurance => "home insurance",
+            CostKind::Maintenance => "maintenance reserve",
+            CostKind::LargeWorks => "large works",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for CostKind {
+    type Err = HomeCalcError;
+
+    fn from_str(value: &str) -> Result<CostKind, HomeCalcError> {
+        match value.trim().to_lowercase().as_str() {
+            "property-tax" => Ok(CostKind::PropertyTax),
+            "co-ownership" => Ok(CostKind::CoOwnership),
+            "insurance" => Ok(CostKind::Insurance),
+            "maintenance" => Ok(CostKind::Maintenance),
+            "large-works" => Ok(CostKind::LargeWorks),
+            _ => Err(HomeCalcError::InvalidValue {
+                name: "owner cost".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+/// How the yearly amount of a cost is set at the purchase
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostBase {
+    /// An amount by year
+    Fixed(u32),
+    /// A ratio of the home value at the purchase by year
+    ValueRatio(f32),
+    /// An amount by square meter of the home and by year
+    PerSquareMeter(f32),
+}
+
+/// A cost of the owner, revised every year from its amount at the purchase
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OwnerCost {
+    pub kind: CostKind,
+    pub base: CostBase,
+    /// Yearly revision of the amount
+    pub indexation: f32,
+}
+
+impl OwnerCost {
+    /// Returns a cost, or an error for an invalid amount or rate
+    ///
+    /// # Arguments
+    /// * `kind` - the kind of cost
+    /// * `base` - how the yearly amount is set at the purchase
+    /// * `indexation` - yearly revision of the amount
+    ///
+    /// # Example
+    /// ```
+    /// // A property tax of 1200 by year rising by 3% a year
+    /// let cost = OwnerCost::new(CostKind::PropertyTax, CostBase::Fixed(1200), 0.03)?;
+    /// ```
+    pub fn new(
+        kind: CostKind,
+        base: CostBase,
+        indexation: f32,
+    ) -> Result<OwnerCost, HomeCalcError> {
+        match base {
+            CostBase::Fixed(_) => (),
+            CostBase::ValueRatio(ratio) => {
+                check_rate("owner cost ratio", ratio)?;
+            }
+            CostBase::PerSquareMeter(amount) => {
+                if !amount.is_finite() || amount < 0_f32 {
+                    return Err(HomeCalcError::InvalidAmount {
+                        name: "owner cost by square meter",
+                        value: amount as f64,
+                    });
+                }
+            }
+        }
+        if !indexation.is_finite() || indexation <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "owner cost indexation",
+                value: indexation,
+            });
+        }
+        Ok(OwnerCost {
+            kind,
+            base,
+            indexation,
+        })
+    }
+
+    /// Return the amount paid at a period
+    ///
+    /// # Arguments
+    /// * `home_value` - the value of the home at the purchase
+    /// * `area` - the area of the home in square meters
+    /// * `periodicity` - number of periods by year
+    /// * `period` - the period, starting at 1
+    pub fn amount_at(&self, home_value: f64, area: u32, periodicity: u8, period: u32) -> f64 {
+        let yearly = match self.base {
+            CostBase::Fixed(amount) => amount as f64,
+            CostBase::ValueRatio(ratio) => ratio as f64 * home_value,
+            CostBase::PerSquareMeter(amount) => amount as f64 * area as f64,
+        };
+        let revisions = period.saturating_sub(1) / periodicity as u32;
+        yearly / periodicity as f64 * (1_f64 + self.indexation as f64).powi(revisions as i32)
+    }
+}
+
+impl fmt::Display for OwnerCost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.base {
+            CostBase::Fixed(amount) => write!(f, "{} of {} by year", self.kind, amount),
+            CostBase::ValueRatio(ratio) => write!(
+                f,
+                "{} of {}% of the home value by year",
+                self.kind,
+                ratio * 100_f32
+            ),
+            CostBase::PerSquareMeter(amount) => {
+                write!(f, "{} of {} by square meter and by year", self.kind, amount)
+            }
+        }?;
+        write!(f, ", revised by {}% every year", self.indexation * 100_f32)
+    }
+}
+
+impl FromStr for OwnerCost {
+    type Err = HomeCalcError;
+
+    /// Parse a cost from `kind:amount:base[:indexation]`, the base being `fixed`, `value` for a
+    /// percentage of the home value or `m2`, and the indexation being in percent
+    fn from_str(value: &str) -> Result<OwnerCost, HomeCalcError> {
+        let invalid = || HomeCalcError::InvalidValue {
+            name: "owner cost".to_string(),
+            value: value.to_string(),
+        };
+        let fields: Vec<&str> = value.split(':').map(str::trim).collect();
+        if fields.len() != 3 && fields.len() != 4 {
+            return Err(invalid());
+        }
+        let amount: f32 = fields[1].parse().map_err(|_| invalid())?;
+        let base = match fields[2] {
+            "fixed" if amount >= 0_f32 && amount.fract() == 0_f32 => {
+                CostBase::Fixed(amount as u32)
+            }
+            "value" => CostBase::ValueRatio(amount / 100_f32),
+            "m2" => CostBase::PerSquareMeter(amount),
+            _ => return Err(invalid()),
+        };
+        let indexation: f32 = match fields.get(3) {
+            Some(indexation) => indexation.parse().map_err(|_| invalid())?,
+            None => 0_f32,
+        };
+        OwnerCost::new(fields[0].parse()?, base, indexation / 100_f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_at() {
+        let tax = OwnerCost::new(CostKind::PropertyTax, CostBase::Fixed(1200), 0.5).unwrap();
+        assert_eq!(100_f64, tax.amount_at(200_000_f64, 50, 12, 12));
+        assert_eq!(150_f64, tax.amount_at(200_000_f64, 50, 12, 13));
+        let maintenance: OwnerCost = "maintenance:0.75:value".parse().unwrap();
+        assert!((maintenance.amount_at(200_000_f64, 50, 12, 100) - 125_f64).abs() < 1e-3);
+        let co_ownership: OwnerCost = "co-ownership:24:m2:0".parse().unwrap();
+        assert_eq!(100_f64, co_ownership.amount_at(200_000_f64, 50, 12, 1));
+    }
+
+    #[test]
+    fn test_parse_owner_cost() {
+        let insurance: OwnerCost = "insurance:300:fixed:2".parse().unwrap();
+        assert_eq!(CostKind::Insurance, insurance.kind);
+        assert_eq!(CostBase::Fixed(300), insurance.base);
+        assert!((insurance.indexation - 0.02).abs() < 1e-6);
+        assert!("insurance:300.5:fixed".parse::<OwnerCost>().is_err());
+        assert!("insurance:300:year".parse::<OwnerCost>().is_err());
+        assert!("pool:300:fixed".parse::<OwnerCost>().is_err());
+        assert!("large-works:-1:m2".parse::<OwnerCost>().is_err());
+    }
+}
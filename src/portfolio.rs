@@ -0,0 +1,306 @@
+use error::{check_rate, HomeCalcError};
+use std::fmt;
+use std::str::FromStr;
+
+/// Tolerance on the sum of the target weights of a portfolio
+const WEIGHTS_TOLERANCE: f32 = 1e-4;
+
+/// An asset class of a portfolio
+#[derive(Debug, Clone, PartialEq)]
+pub struct Asset {
+    pub name: String,
+    /// Expected yield by year
+    pub yield_rate: f32,
+    /// Standard deviation of the yearly returns, used by simulations
+    pub volatility: f32,
+    /// Target share of the portfolio
+    pub weight: f32,
+}
+
+impl Asset {
+    /// Returns an asset class, or an error for an invalid rate
+    ///
+    /// # Arguments
+    /// * `name` - name of the asset class
+    /// * `yield_rate` - expected yield by year
+    /// * `volatility` - standard deviation of the yearly returns
+    /// * `weight` - target share of the portfolio
+    pub fn new(
+        name: &str,
+        yield_rate: f32,
+        volatility: f32,
+        weight: f32,
+    ) -> Result<Asset, HomeCalcError> {
+        if !yield_rate.is_finite() || yield_rate <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "asset yield rate",
+                value: yield_rate,
+            });
+        }
+        check_rate("asset volatility", volatility)?;
+        check_rate("asset weight", weight)?;
+        Ok(Asset {
+            name: name.to_string(),
+            yield_rate,
+            volatility,
+            weight,
+        })
+    }
+}
+
+/// When a portfolio is brought back to its target allocation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rebalancing {
+    /// Only the contributions go to the underweight assets
+    Never,
+    /// Every number of periods
+    Periodic(u32),
+    /// As soon as the share of an asset drifts from its target by more than a ratio
+    Threshold(f32),
+}
+
+/// A portfolio of several asset classes with a target allocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Portfolio {
+    pub assets: Vec<Asset>,
+    pub rebalancing: Rebalancing,
+}
+
+impl Portfolio {
+    /// Returns a portfolio, or an error when the target weights don't sum to 1
+    ///
+    /// # Arguments
+    /// * `assets` - the asset classes
+    /// * `rebalancing` - the rebalancing rule
+    ///
+    /// # Example
+    /// ```
+    /// let portfolio = Portfolio::new(
+    ///     vec![
+    ///         Asset::new("stocks", 0.07, 0.16, 0.6)?,
+    ///         Asset::new("bonds", 0.03, 0.05, 0.4)?,
+    ///     ],
+    ///     Rebalancing::Periodic(12),
+    /// )?;
+    /// ```
+    pub fn new(assets: Vec<Asset>, rebalancing: Rebalancing) -> Result<Portfolio, HomeCalcError> {
+        let total: f32 = assets.iter().map(|asset| asset.weight).sum();
+        if assets.is_empty() || (total - 1_f32).abs() > WEIGHTS_TOLERANCE {
+            return Err(HomeCalcError::InvalidRate {
+                name: "sum of the asset weights",
+                value: total,
+            });
+        }
+        match rebalancing {
+            Rebalancing::Periodic(0) => {
+                return Err(HomeCalcError::InvalidDuration {
+                    name: "rebalancing periods",
+                    value: 0,
+                })
+            }
+            Rebalancing::Threshold(threshold) => {
+                check_rate("rebalancing threshold", threshold)?;
+            }
+            _ => (),
+        }
+        Ok(Portfolio {
+            assets,
+            rebalancing,
+        })
+    }
+
+    /// Return the expected yield by year of the target allocation
+    pub fn yield_rate(&self) -> f32 {
+        self.assets
+            .iter()
+            .map(|asset| asset.weight * asset.yield_rate)
+            .sum()
+    }
+
+    /// Return the holdings of an amount split along the target allocation
+    ///
+    /// # Arguments
+    /// * `amount` - the amount
+    pub fn split(&self, amount: f64) -> Vec<f64> {
+        self.assets
+            .iter()
+            .map(|asset| amount * asset.weight as f64)
+            .collect()
+    }
+
+    /// Return the holdings once a contribution is added, the underweight assets first
+    ///
+    /// # Arguments
+    /// * `holdings` - the amount held in each asset
+    /// * `contribution` - the amount to add
+    pub fn allocate(&self, holdings: &[f64], contribution: f64) -> Vec<f64> {
+        let total = holdings.iter().sum::<f64>() + contribution;
+        let shortfalls: Vec<f64> = self
+            .split(total)
+            .iter()
+            .zip(holdings)
+            .map(|(target, holding)| (target - holding).max(0_f64))
+            .collect();
+        // The shortfalls add up to at least the contribution, the overweight assets get nothing
+        let shortfall: f64 = shortfalls.iter().sum();
+        if shortfall <= 0_f64 {
+            return holdings.to_vec();
+        }
+        holdings
+            .iter()
+            .zip(shortfalls)
+            .map(|(holding, missing)| holding + contribution * missing / shortfall)
+            .collect()
+    }
+
+    /// Return the holdings at the end of a period once the rebalancing rule applies
+    ///
+    /// # Arguments
+    /// * `holdings` - the amount held in each asset
+    /// * `period` - the period, starting at 1
+    pub fn rebalance(&self, holdings: Vec<f64>, period: u32) -> Vec<f64> {
+        let total: f64 = holdings.iter().sum();
+        let rebalance = match self.rebalancing {
+            Rebalancing::Never => false,
+            Rebalancing::Periodic(every) => period.is_multiple_of(every),
+            Rebalancing::Threshold(threshold) => {
+                total > 0_f64 && self.assets.iter().zip(&holdings).any(|(asset, holding)| {
+                    (holding / total - asset.weight as f64).abs() > threshold as f64
+                })
+            }
+        };
+        if rebalance {
+            self.split(total)
+        } else {
+            holdings
+        }
+    }
+}
+
+impl fmt::Display for Rebalancing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Rebalancing::Never => write!(f, "never rebalanced"),
+            Rebalancing::Periodic(every) => write!(f, "rebalanced every {} periods", every),
+            Rebalancing::Threshold(threshold) => write!(
+                f,
+                "rebalanced beyond a drift of {:.1}%",
+                threshold * 100_f32
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Portfolio {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for asset in &self.assets {
+            write!(
+                f,
+                "{:.1}% {} ({:.2}%), ",
+                asset.weight * 100_f32,
+                asset.name,
+                asset.yield_rate * 100_f32
+            )?;
+        }
+        write!(
+            f,
+            "{}, {:.2}% expected by year",
+            self.rebalancing,
+            self.yield_rate() * 100_f32
+        )
+    }
+}
+
+fn invalid(name: &str, value: &str) -> HomeCalcError {
+    HomeCalcError::InvalidValue {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+impl FromStr for Asset {
+    type Err = HomeCalcError;
+
+    /// Parse an asset class from `name:yield:volatility:weight`, the rates being in percent
+    fn from_str(value: &str) -> Result<Asset, HomeCalcError> {
+        let fields: Vec<&str> = value.split(':').map(str::trim).collect();
+        if fields.len() != 4 || fields[0].is_empty() {
+            return Err(invalid("asset", value));
+        }
+        let mut rates = fields[1..].iter().map(|field| field.parse::<f32>());
+        match (rates.next(), rates.next(), rates.next()) {
+            (Some(Ok(yield_rate)), Some(Ok(volatility)), Some(Ok(weight))) => Asset::new(
+                fields[0],
+                yield_rate / 100_f32,
+                volatility / 100_f32,
+                weight / 100_f32,
+            ),
+            _ => Err(invalid("asset", value)),
+        }
+    }
+}
+
+impl FromStr for Rebalancing {
+    type Err = HomeCalcError;
+
+    /// Parse a rebalancing rule from `never`, `every:<periods>` or `threshold:<percent>`
+    fn from_str(value: &str) -> Result<Rebalancing, HomeCalcError> {
+        let mut fields = value.splitn(2, ':');
+        match (fields.next(), fields.next()) {
+            (Some("never"), None) => Ok(Rebalancing::Never),
+            (Some("every"), Some(every)) => every
+                .parse()
+                .map(Rebalancing::Periodic)
+                .map_err(|_| invalid("rebalancing", value)),
+            (Some("threshold"), Some(threshold)) => threshold
+                .parse::<f32>()
+                .map(|threshold| Rebalancing::Threshold(threshold / 100_f32))
+                .map_err(|_| invalid("rebalancing", value)),
+            _ => Err(invalid("rebalancing", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn three_to_one(rebalancing: Rebalancing) -> Portfolio {
+        Portfolio::new(
+            vec![
+                Asset::new("stocks", 0.08, 0.16, 0.75).unwrap(),
+                Asset::new("bonds", 0.04, 0.05, 0.25).unwrap(),
+            ],
+            rebalancing,
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_allocate() {
+        let portfolio = three_to_one(Rebalancing::Never);
+        assert!((portfolio.yield_rate() - 0.07).abs() < 1e-6);
+        // Everything goes to the underweight bonds
+        assert_eq!(vec![800_f64, 200_f64], portfolio.allocate(&[800_f64, 150_f64], 50_f64));
+        // Both assets are underweight, each gets its shortfall
+        assert_eq!(vec![825_f64, 275_f64], portfolio.allocate(&[700_f64, 200_f64], 200_f64));
+        let half = vec![Asset::new("stocks", 0.05, 0.1, 0.5).unwrap()];
+        assert!(Portfolio::new(half, Rebalancing::Never).is_err());
+    }
+
+    #[test]
+    fn test_rebalance() {
+        let periodic = three_to_one(Rebalancing::Periodic(12));
+        assert_eq!(vec![800_f64, 200_f64], periodic.rebalance(vec![800_f64, 200_f64], 11));
+        assert_eq!(vec![750_f64, 250_f64], periodic.rebalance(vec![800_f64, 200_f64], 12));
+        let threshold = three_to_one(Rebalancing::Threshold(0.05));
+        assert_eq!(vec![770_f64, 230_f64], threshold.rebalance(vec![770_f64, 230_f64], 1));
+        assert_eq!(vec![750_f64, 250_f64], threshold.rebalance(vec![850_f64, 150_f64], 1));
+        assert!(Portfolio::new(Vec::new(), Rebalancing::Periodic(0)).is_err());
+        assert_eq!(Rebalancing::Periodic(12), "every:12".parse().unwrap());
+        assert_eq!(Rebalancing::Threshold(0.125), "threshold:12.5".parse().unwrap());
+        assert!("every".parse::<Rebalancing>().is_err());
+        let stocks: Asset = "stocks:6.25:15:75".parse().unwrap();
+        assert_eq!(Asset::new("stocks", 0.0625, 0.15, 0.75).unwrap(), stocks);
+        assert!("stocks:6:15".parse::<Asset>().is_err());
+    }
+}
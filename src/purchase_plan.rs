@@ -0,0 +1,151 @@
+use error::{check_rate, HomeCalcError};
+use investment::Investment;
+use std::iter;
+
+/// A household saving toward the purchase of a home whose price rises meanwhile
+#[derive(Clone)]
+pub struct PurchasePlan {
+    /// The savings and the regular savings capacity, invested until the purchase
+    pub savings: Investment,
+    /// Price of the home today, the purchase charges excluded
+    pub price: u32,
+    /// Appreciation of the home price by year
+    pub appreciation: f32,
+    /// Purchase charges, as a ratio of the price
+    pub purchase_charges: f32,
+    /// Share of the price to pay with the savings, on top of the purchase charges
+    pub down_payment: f32,
+}
+
+/// The purchase made at the end of a saving phase
+#[derive(Debug, Clone, PartialEq)]
+pub struct Purchase {
+    pub period: u32,
+    /// Price of the home at the purchase, the purchase charges excluded
+    pub price: f64,
+    /// Savings available after tax
+    pub savings: f64,
+    /// Down payment plus purchase charges needed
+    pub required: f64,
+    /// Loan covering the rest of the cost once all the savings are used
+    pub loan: f64,
+}
+
+impl PurchasePlan {
+    /// Returns a purchase plan, or an error for an invalid rate
+    ///
+    /// # Arguments
+    /// * `savings` - the savings invested until the purchase
+    /// * `price` - price of the home today
+    /// * `appreciation` - appreciation of the home price by year
+    /// * `purchase_charges` - purchase charges as a ratio of the price
+    /// * `down_payment` - share of the price paid with the savings
+    ///
+    /// # Example
+    /// ```
+    /// // 20k saved and 800 a month at 3% for a 300k home rising by 2% a year
+    /// let savings = Investment::new(20_000, 12, 0.03, 800)?;
+    /// let plan = PurchasePlan::new(savings, 300_000, 0.02, 0.08, 0.1)?;
+    /// let purchase = plan.earliest_purchase(360)?;
+    /// ```
+    pub fn new(
+        savings: Investment,
+        price: u32,
+        appreciation: f32,
+        purchase_charges: f32,
+        down_payment: f32,
+    ) -> Result<PurchasePlan, HomeCalcError> {
+        if !appreciation.is_finite() || appreciation <= -1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "home appreciation",
+                value: appreciation,
+            });
+        }
+        check_rate("purchase charges", purchase_charges)?;
+        check_rate("down payment", down_payment)?;
+        if down_payment > 1_f32 {
+            return Err(HomeCalcError::InvalidRate {
+                name: "down payment",
+                value: down_payment,
+            });
+        }
+        Ok(PurchasePlan {
+            savings,
+            price,
+            appreciation,
+            purchase_charges,
+            down_payment,
+        })
+    }
+
+    /// Return the price of the home at a period, the appreciation being compounded
+    ///
+    /// # Arguments
+    /// * `period` - the period
+    pub fn price_at(&self, period: u32) -> f64 {
+        let years = period as f64 / self.savings.periodicity as f64;
+        self.price as f64 * (1_f64 + self.appreciation as f64).powf(years)
+    }
+
+    /// Return the down payment plus the purchase charges needed at a period
+    ///
+    /// # Arguments
+    /// * `period` - the period
+    pub fn required_at(&self, period: u32) -> f64 {
+        self.price_at(period) * (self.down_payment as f64 + self.purchase_charges as f64)
+    }
+
+    fn purchase(&self, period: u32, savings: f64) -> Purchase {
+        let price = self.price_at(period);
+        Purchase {
+            period,
+            price,
+            savings,
+            required: self.required_at(period),
+            loan: (price * (1_f64 + self.purchase_charges as f64) - savings).max(0_f64),
+        }
+    }
+
+    /// Return the earliest purchase, when the savings after tax cover the down payment and the
+    /// purchase charges
+    ///
+    /// # Arguments
+    /// * `max_period` - the last period to look at
+    pub fn earliest_purchase(&self, max_period: u32) -> Result<Purchase, HomeCalcError> {
+        iter::once((0, self.savings.capital as f64))
+            .chain(self.savings.schedule().map(|row| (row.period, row.after_tax)))
+            .take(max_period as usize + 1)
+            .find(|&(period, savings)| savings >= self.required_at(period))
+            .map(|(period, savings)| self.purchase(period, savings))
+            .ok_or(HomeCalcError::NoSolution("purchase period"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_earliest_purchase() {
+        // Nothing earned, 1000 a month for a home of 200k at a fixed price: 50 months
+        let savings = Investment::new(0, 12, 0_f32, 1000).unwrap();
+        let plan = PurchasePlan::new(savings.clone(), 200_000, 0_f32, 0.125, 0.125).unwrap();
+        let purchase = plan.earliest_purchase(600).unwrap();
+        assert_eq!(50, purchase.period);
+        assert_eq!(50_000_f64, purchase.required);
+        assert_eq!(175_000_f64, purchase.loan);
+
+        // The appreciation delays the purchase
+        let rising = PurchasePlan::new(savings.clone(), 200_000, 0.05, 0.08, 0.1).unwrap();
+        let purchase = rising.earliest_purchase(600).unwrap();
+        assert!(purchase.period > 36);
+        assert!(purchase.savings >= purchase.required);
+        assert!((rising.price_at(24) - 220_500_f64).abs() < 1e-2);
+
+        // Prices rising faster than the savings are never caught up
+        let out_of_reach = PurchasePlan::new(savings, 1_000_000, 0.2, 0.08, 0.1).unwrap();
+        assert!(out_of_reach.earliest_purchase(600).is_err());
+        let savings = Investment::new(0, 12, 0_f32, 1000).unwrap();
+        assert!(PurchasePlan::new(savings, 200_000, 0_f32, 0.08, 1.5).is_err());
+    }
+}
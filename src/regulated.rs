@@ -0,0 +1,413 @@
+use csv_file::records;
+use date::Date;
+use error::{check_rate, HomeCalcError};
+use investment::Investment;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Number of fortnights (quinzaines) in a year
+const FORTNIGHTS_BY_YEAR: f64 = 24_f64;
+/// Rates of the Livret A and the LDDS since 2009, from the date they apply
+const LIVRET_A_RATES: [(i32, u32, f32); 13] = [
+    (2009, 8, 0.0125),
+    (2011, 2, 0.02),
+    (2011, 8, 0.0225),
+    (2013, 2, 0.0175),
+    (2013, 8, 0.0125),
+    (2014, 8, 0.01),
+    (2015, 8, 0.0075),
+    (2020, 2, 0.005),
+    (2022, 2, 0.01),
+    (2022, 8, 0.02),
+    (2023, 2, 0.03),
+    (2025, 2, 0.024),
+    (2025, 8, 0.017),
+];
+/// Rates of the PEL since 2015, fixed at the opening of the plan
+const PEL_RATES: [(i32, u32, f32); 7] = [
+    (2015, 2, 0.02),
+    (2016, 2, 0.015),
+    (2016, 8, 0.01),
+    (2023, 1, 0.02),
+    (2023, 7, 0.0225),
+    (2024, 1, 0.0225),
+    (2025, 1, 0.0175),
+];
+
+/// A regulated savings account
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountKind {
+    /// Livret A
+    LivretA,
+    /// Livret de développement durable et solidaire
+    Ldds,
+    /// Plan d'épargne logement, whose rate is the one at the opening
+    Pel,
+    /// Compte d'épargne logement, paying two thirds of the Livret A rate
+    Cel,
+}
+
+impl AccountKind {
+    /// Return the maximum amount of deposits, capitalized interest excluded
+    pub fn ceiling(&self) -> f64 {
+        match *self {
+            AccountKind::LivretA => 22_950_f64,
+            AccountKind::Ldds => 12_000_f64,
+            AccountKind::Pel => 61_200_f64,
+            AccountKind::Cel => 15_300_f64,
+        }
+    }
+
+    /// Return the rates set by decree for this kind of account
+    pub fn rates(&self) -> RateHistory {
+        let (rates, ratio): (&[(i32, u32, f32)], f32) = match *self {
+            AccountKind::LivretA | AccountKind::Ldds => (&LIVRET_A_RATES, 1_f32),
+            AccountKind::Pel => (&PEL_RATES, 1_f32),
+            AccountKind::Cel => (&LIVRET_A_RATES, 2_f32 / 3_f32),
+        };
+        // The rate of the CEL is rounded to the nearest quarter of a point
+        let round = |rate: f32| {
+            if ratio == 1_f32 {
+                rate
+            } else {
+                (rate * ratio * 400_f32).round() / 400_f32
+            }
+        };
+        RateHistory::new(
+            rates
+                .iter()
+                .filter_map(|&(year, month, rate)| {
+                    Date::new(year, month, 1).map(|date| (date, round(rate)))
+                }).collect(),
+        )
+    }
+}
+
+impl fmt::Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AccountKind::LivretA => write!(f, "Livret A"),
+            AccountKind::Ldds => write!(f, "LDDS"),
+            AccountKind::Pel => write!(f, "PEL"),
+            AccountKind::Cel => write!(f, "CEL"),
+        }
+    }
+}
+
+impl FromStr for AccountKind {
+    type Err = HomeCalcError;
+
+    fn from_str(value: &str) -> Result<AccountKind, HomeCalcError> {
+        match value {
+            "livret-a" => Ok(AccountKind::LivretA),
+            "ldds" => Ok(AccountKind::Ldds),
+            "pel" => Ok(AccountKind::Pel),
+            "cel" => Ok(AccountKind::Cel),
+            _ => Err(HomeCalcError::InvalidValue {
+                name: "account".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+/// The rates of an account over time, each rate applying from its date
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateHistory {
+    pub rates: Vec<(Date, f32)>,
+}
+
+impl RateHistory {
+    /// Returns a rate history given its rates, in any order
+    ///
+    /// # Arguments
+    /// * `rates` - dates from which each rate by year applies
+    pub fn new(mut rates: Vec<(Date, f32)>) -> RateHistory {
+        rates.sort_by_key(|&(date, _)| date);
+        RateHistory { rates }
+    }
+
+    /// Return the rate by year in force at a date, the first rate before the history
+    ///
+    /// # Arguments
+    /// * `date` - the date
+    pub fn rate_at(&self, date: Date) -> f32 {
+        self.rates
+            .iter()
+            .take_while(|&&(from, _)| from <= date)
+            .last()
+            .or_else(|| self.rates.first())
+            .map_or(0_f32, |&(_, rate)| rate)
+    }
+}
+
+/// Load a rate history from a CSV file
+///
+/// # Arguments
+/// * `path` - path of the file
+pub fn load_rates(path: &Path) -> Result<RateHistory, HomeCalcError> {
+    parse_rates(&fs::read_to_string(path)?)
+}
+
+/// Parse a rate history from CSV lines of `date;rate`, the rate being in percent
+///
+/// # Arguments
+/// * `content` - the CSV content
+pub fn parse_rates(content: &str) -> Result<RateHistory, HomeCalcError> {
+    let mut rates = Vec::new();
+    for (index, record) in records(content).iter().enumerate() {
+        let date = match record.date(0) {
+            Some(date) => date,
+            None if index == 0 => continue,
+            None => return Err(record.error("invalid date").into()),
+        };
+        let rate = record
+            .number(1)
+            .ok_or_else(|| record.error("invalid rate"))?;
+        rates.push((date, check_rate("account rate", (rate / 100_f64) as f32)?));
+    }
+    if rates.is_empty() {
+        return Err(HomeCalcError::InvalidValue {
+            name: "account rates".to_string(),
+            value: String::new(),
+        });
+    }
+    Ok(RateHistory::new(rates))
+}
+
+/// The savings on a regulated account at the end of a plan
+pub struct Savings {
+    /// Balance of the account, interest of the current year included
+    pub balance: f64,
+    /// Total interest earned
+    pub interest: f64,
+    /// Part of each monthly deposit refused by the ceiling
+    pub overflows: Vec<f64>,
+    /// Capital of the investment receiving the overflows
+    pub overflow_capital: f64,
+}
+
+/// A regulated savings account fed every month
+#[derive(Clone)]
+pub struct RegulatedAccount {
+    pub kind: AccountKind,
+    pub balance: u32,
+    pub opening: Date,
+    pub rates: RateHistory,
+    /// The investment receiving the deposits over the ceiling, they are kept aside when none
+    pub overflow: Option<Investment>,
+}
+
+impl RegulatedAccount {
+    /// Returns an account with the rates set by decree, or an error when the balance is over the
+    /// ceiling
+    ///
+    /// # Arguments
+    /// * `kind` - the kind of account
+    /// * `balance` - the balance at the opening of the plan
+    /// * `opening` - the date of the opening of the plan
+    ///
+    /// # Example
+    /// ```
+    /// let opening = Date::new(2024, 1, 5).unwrap();
+    /// let livret_a = RegulatedAccount::new(AccountKind::LivretA, 5000, opening)?;
+    /// let savings = livret_a.savings(300, 36);
+    /// ```
+    pub fn new(
+        kind: AccountKind,
+        balance: u32,
+        opening: Date,
+    ) -> Result<RegulatedAccount, HomeCalcError> {
+        if balance as f64 > kind.ceiling() {
+            return Err(HomeCalcError::InvalidValue {
+                name: format!("balance over the {} ceiling", kind),
+                value: balance.to_string(),
+            });
+        }
+        Ok(RegulatedAccount {
+            kind,
+            balance,
+            opening,
+            rates: kind.rates(),
+            overflow: None,
+        })
+    }
+
+    /// Returns the account with another rate history
+    ///
+    /// # Arguments
+    /// * `rates` - the rate history
+    pub fn with_rates(mut self, rates: RateHistory) -> RegulatedAccount {
+        self.rates = rates;
+        self
+    }
+
+    /// Returns the account with the deposits over the ceiling redirected to an investment
+    ///
+    /// # Arguments
+    /// * `overflow` - the investment, with a monthly periodicity
+    pub fn with_overflow(mut self, overflow: Investment) -> RegulatedAccount {
+        self.overflow = Some(overflow);
+        self
+    }
+
+    /// Return the rate by year applied at a date, the one at the opening for a PEL
+    ///
+    /// # Arguments
+    /// * `date` - the date
+    pub fn rate_at(&self, date: Date) -> f32 {
+        match self.kind {
+            AccountKind::Pel => self.rates.rate_at(self.opening),
+            _ => self.rates.rate_at(date),
+        }
+    }
+
+    /// Return the savings after monthly deposits, the account being closed after the last one
+    ///
+    /// Interest follows the quinzaine rule: a deposit earns interest from the 1st or the 16th
+    /// following it, the fortnight of the closing earns nothing, and the interest is
+    /// capitalized every 31st of December.
+    ///
+    /// # Arguments
+    /// * `deposit` - the amount deposited every month, from a month after the opening
+    /// * `n_months` - number of months of the plan
+    pub fn savings(&self, deposit: u32, n_months: u32) -> Savings {
+        let closing = self.opening.add_months(n_months);
+        let mut balance = self.balance as f64;
+        // The ceiling applies to the deposits, the capitalized interest may exceed it
+        let mut deposited = self.balance as f64;
+        let mut accrued = 0_f64;
+        let mut interest = 0_f64;
+        let mut overflows = Vec::with_capacity(n_months as usize);
+        let mut next_deposit = 1;
+        let mut fortnight = fortnight_after(self.opening);
+        loop {
+            // The deposits made before the fortnight earn its interest
+            while next_deposit <= n_months && self.opening.add_months(next_deposit) < fortnight {
+                let room = (self.kind.ceiling() - deposited).max(0_f64);
+                let accepted = (deposit as f64).min(room);
+                deposited += accepted;
+                balance += accepted;
+                overflows.push(deposit as f64 - accepted);
+                next_deposit += 1;
+            }
+            if fortnight.month == 1 && fortnight.day == 1 {
+                balance += accrued;
+                accrued = 0_f64;
+            }
+            let next = fortnight_after(fortnight);
+            if next > closing {
+                break;
+            }
+            let earned = balance * self.rate_at(fortnight) as f64 / FORTNIGHTS_BY_YEAR;
+            accrued += earned;
+            interest += earned;
+            fortnight = next;
+        }
+        // The last deposits are made in the fortnight of the closing
+        for _ in next_deposit..=n_months {
+            let accepted = (deposit as f64).min((self.kind.ceiling() - deposited).max(0_f64));
+            deposited += accepted;
+            balance += accepted;
+            overflows.push(deposit as f64 - accepted);
+        }
+        let overflow_capital = match self.overflow {
+            Some(ref invest) => invest.capital_with_additions(&overflows),
+            None => overflows.iter().sum(),
+        };
+        Savings {
+            balance: balance + accrued,
+            interest,
+            overflows,
+            overflow_capital,
+        }
+    }
+}
+
+/// Return the first fortnight starting after a date
+fn fortnight_after(date: Date) -> Date {
+    if date.day < 16 {
+        Date { day: 16, ..date }
+    } else {
+        Date { day: 1, ..date }.add_months(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use date::date;
+
+    #[test]
+    fn test_rates() {
+        let rates = AccountKind::LivretA.rates();
+        assert_eq!(0.03, rates.rate_at(date(2024, 6, 1)));
+        assert_eq!(0.02, rates.rate_at(date(2023, 1, 31)));
+        assert_eq!(0.0125, rates.rate_at(date(2000, 1, 1)));
+        let rates = parse_rates("date;rate\n2024-01-01;3\n2020-01-01;0,5\n").unwrap();
+        assert_eq!(0.005, rates.rate_at(date(2023, 12, 31)));
+        assert!(parse_rates("date;rate\n").is_err());
+        assert_eq!(AccountKind::Pel, "pel".parse().unwrap());
+        let cel = AccountKind::Cel.rates();
+        assert_eq!(0.02, cel.rate_at(date(2024, 6, 1)));
+        assert_eq!(0.0125, cel.rate_at(date(2025, 9, 1)));
+    }
+
+    #[test]
+    fn test_quinzaine() {
+        // 1000 deposited on the 5th of January earns 23 fortnights in the year at 3%
+        let flat = RateHistory::new(vec![(date(2020, 1, 1), 0.03)]);
+        let account = RegulatedAccount::new(AccountKind::LivretA, 1000, date(2024, 1, 5))
+            .unwrap()
+            .with_rates(flat.clone());
+        let savings = account.savings(0, 12);
+        assert!((savings.interest - 1000_f64 * 0.03 * 23_f64 / 24_f64).abs() < 1e-4);
+        assert!((savings.balance - 1028.75).abs() < 1e-4);
+
+        // Opened on the 20th and closed on the 20th of the next month, only the first fortnight
+        // of the month is complete
+        let account = RegulatedAccount::new(AccountKind::LivretA, 1000, date(2024, 1, 20))
+            .unwrap()
+            .with_rates(flat.clone());
+        let savings = account.savings(0, 1);
+        assert!((savings.interest - 1.25).abs() < 1e-4);
+
+        // The interest is capitalized at the end of the year
+        let account = RegulatedAccount::new(AccountKind::LivretA, 1000, date(2023, 12, 31))
+            .unwrap()
+            .with_rates(flat);
+        let savings = account.savings(0, 24);
+        let first_year = 1000_f64 * 0.03;
+        let expected = first_year + (1000_f64 + first_year) * 0.03 * 23_f64 / 24_f64;
+        assert!((savings.interest - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_ceiling() {
+        let flat = RateHistory::new(vec![(date(2020, 1, 1), 0_f32)]);
+        let account = RegulatedAccount::new(AccountKind::Ldds, 11_000, date(2024, 1, 5))
+            .unwrap()
+            .with_rates(flat);
+        let savings = account.clone().savings(400, 4);
+        assert_eq!(12_000_f64, savings.balance);
+        assert_eq!(vec![0_f64, 0_f64, 200_f64, 400_f64], savings.overflows);
+        assert_eq!(600_f64, savings.overflow_capital);
+
+        let invest = Investment::new(0, 12, 0.12, 0).unwrap();
+        let savings = account.with_overflow(invest).savings(400, 4);
+        assert!((savings.overflow_capital - (200_f64 * 1.01 + 400_f64)).abs() < 1e-6);
+        assert!(RegulatedAccount::new(AccountKind::Ldds, 13_000, date(2024, 1, 5)).is_err());
+
+        // The interest takes the balance over the ceiling, the deposits go on up to it
+        let high = RateHistory::new(vec![(date(2020, 1, 1), 0.05)]);
+        let account = RegulatedAccount::new(AccountKind::LivretA, 22_000, date(2024, 1, 5))
+            .unwrap()
+            .with_rates(high);
+        let savings = account.savings(50, 36);
+        assert!(savings.balance > 22_950_f64 + savings.interest - 1e-6);
+        assert_eq!(vec![0_f64; 19], savings.overflows[..19].to_vec());
+        assert_eq!(36_f64 * 50_f64 - 950_f64, savings.overflows.iter().sum::<f64>());
+    }
+}
@@ -0,0 +1,88 @@
+use inflation::Inflation;
+
+/// A plan describing how the rent of a tenant evolves over time
+///
+/// The rent is revised at each anniversary of the lease along an index, and a new lease starts
+/// when the tenant moves.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RentPlan {
+    /// Yearly revision of the rent, the rent never changes when none
+    pub indexation: Option<Inflation>,
+    /// New rents `(from period, rent)`, e.g. when moving to a bigger flat
+    pub changes: Vec<(u32, u32)>,
+}
+
+impl RentPlan {
+    /// Returns the plan with the rent revised every year
+    ///
+    /// # Arguments
+    /// * `indexation` - the variation of the index of each year
+    ///
+    /// # Example
+    /// ```
+    /// // Revised by 2% every year, then 1300 in a bigger flat from the 5th year
+    /// let plan = RentPlan::default()
+    ///     .with_indexation(Inflation::constant(0.02)?)
+    ///     .with_change(49, 1300);
+    /// ```
+    pub fn with_indexation(mut self, indexation: Inflation) -> RentPlan {
+        self.indexation = Some(indexation);
+        self
+    }
+
+    /// Set a new rent from a period, the revisions restart from there
+    ///
+    /// # Arguments
+    /// * `from` - the first period of the new rent
+    /// * `amount` - the new rent by period
+    pub fn with_change(mut self, from: u32, amount: u32) -> RentPlan {
+        self.changes.push((from, amount));
+        self
+    }
+
+    /// Return the rent of a period
+    ///
+    /// # Arguments
+    /// * `rent` - the rent by period at the start of the plan
+    /// * `periodicity` - number of periods by year
+    /// * `period` - the period, starting at 1
+    pub fn rent_at(&self, rent: u32, periodicity: u8, period: u32) -> f64 {
+        let (start, amount) = self
+            .changes
+            .iter()
+            .filter(|&&(from, _)| from <= period)
+            .max_by_key(|&&(from, _)| from)
+            .cloned()
+            .unwrap_or((1, rent));
+        let indexation = match self.indexation {
+            Some(ref indexation) => indexation,
+            None => return amount as f64,
+        };
+        // Each revision follows the index over the year of the computation just elapsed
+        let periodicity = periodicity as u32;
+        let first_year = start.saturating_sub(1) / periodicity;
+        let revisions = period.saturating_sub(start) / periodicity;
+        (first_year..first_year + revisions).fold(amount as f64, |rent, year| {
+            rent * (1_f64 + indexation.rate_of_year(year) as f64)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rent_at() {
+        let plan = RentPlan::default()
+            .with_indexation(Inflation::new(vec![0.5, 0.25]).unwrap())
+            .with_change(31, 2000);
+        assert_eq!(1000_f64, RentPlan::default().rent_at(1000, 12, 100));
+        assert_eq!(1000_f64, plan.rent_at(1000, 12, 12));
+        assert_eq!(1500_f64, plan.rent_at(1000, 12, 13));
+        assert_eq!(1875_f64, plan.rent_at(1000, 12, 25));
+        // The new lease starts in the 3rd year and is revised at its anniversary
+        assert_eq!(2000_f64, plan.rent_at(1000, 12, 42));
+        assert_eq!(2500_f64, plan.rent_at(1000, 12, 43));
+    }
+}
@@ -0,0 +1,57 @@
+/// Maximum number of iterations for root finding
+const MAX_ITERATIONS: u32 = 200;
+
+/// Find a root of a function in an interval by bisection
+///
+/// The function must change sign over the interval, `None` is returned otherwise.
+///
+/// # Arguments
+/// * `f` - the function
+/// * `low` - the lower bound of the interval
+/// * `high` - the higher bound of the interval
+/// * `tolerance` - the width of the interval under which the root is found
+///
+/// # Example
+/// ```
+/// let sqrt_2 = bisect(|x| x * x - 2_f64, 0_f64, 2_f64, 1e-10);
+/// ```
+pub fn bisect<F: Fn(f64) -> f64>(f: F, low: f64, high: f64, tolerance: f64) -> Option<f64> {
+    let (mut low, mut high) = (low, high);
+    let mut f_low = f(low);
+    let f_high = f(high);
+    if f_low == 0_f64 {
+        return Some(low);
+    }
+    if f_high == 0_f64 {
+        return Some(high);
+    }
+    if f_low.is_nan() || f_high.is_nan() || f_low.signum() == f_high.signum() {
+        return None;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let middle = (low + high) / 2_f64;
+        let f_middle = f(middle);
+        if f_middle == 0_f64 || (high - low).abs() / 2_f64 < tolerance {
+            return Some(middle);
+        }
+        if f_middle.signum() == f_low.signum() {
+            low = middle;
+            f_low = f_middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some((low + high) / 2_f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bisect() {
+        let root = bisect(|x| x * x - 2_f64, 0_f64, 2_f64, 1e-10).unwrap();
+        assert!((root - 2_f64.sqrt()).abs() < 1e-9);
+        assert_eq!(None, bisect(|x| x * x + 1_f64, -1_f64, 1_f64, 1e-10));
+    }
+}
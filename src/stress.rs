@@ -0,0 +1,321 @@
+use comparators::{HomeInvest, PERIODICITY};
+use date::Date;
+use error::{check_amount, HomeCalcError};
+use indexed_loan::{IndexedLoan, RateIndex};
+use loan::Loan;
+use std::fmt;
+
+/// The maximum ratio of the income that can be spent on loans (HCSF recommendation)
+pub const DEBT_RATIO_THRESHOLD: f64 = 0.35;
+
+/// Check that the income of the borrowers is positive, the debt ratio being relative to it
+///
+/// # Arguments
+/// * `monthly_income` - the income of the borrowers by month
+pub fn check_income(monthly_income: f64) -> Result<f64, HomeCalcError> {
+    if check_amount("monthly income", monthly_income)? == 0_f64 {
+        return Err(HomeCalcError::InvalidAmount {
+            name: "monthly income",
+            value: monthly_income,
+        });
+    }
+    Ok(monthly_income)
+}
+
+/// A shock applied to interest rates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shock {
+    /// Rates move up at once
+    Parallel(f32),
+    /// Rates move up linearly until they reach the total move after a number of months
+    Ramp { total: f32, months: u32 },
+}
+
+impl Shock {
+    /// Return the move of the rates some months after the shock starts
+    ///
+    /// # Arguments
+    /// * `months` - number of months since the start of the shock
+    pub fn shift_at(&self, months: u32) -> f32 {
+        match *self {
+            Shock::Parallel(shift) => shift,
+            Shock::Ramp { total, months: ramp } => {
+                if months >= ramp {
+                    total
+                } else {
+                    total * months as f32 / ramp as f32
+                }
+            }
+        }
+    }
+
+    /// Return the largest move of the rates
+    pub fn peak(&self) -> f32 {
+        match *self {
+            Shock::Parallel(shift) => shift,
+            Shock::Ramp { total, .. } => total,
+        }
+    }
+}
+
+impl fmt::Display for Shock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Shock::Parallel(shift) => {
+                if shift == 0_f32 {
+                    write!(f, "none")
+                } else {
+                    write!(f, "{:+.2}% parallel", shift * 100_f32)
+                }
+            }
+            Shock::Ramp { total, months } => {
+                write!(f, "{:+.2}% ramp over {} months", total * 100_f32, months)
+            }
+        }
+    }
+}
+
+/// Return the predefined shocks: +1%, +2% and +3% parallel, +2% over 2 years and +3% over 3 years
+pub fn default_shocks() -> Vec<Shock> {
+    vec![
+        Shock::Parallel(0.01),
+        Shock::Parallel(0.02),
+        Shock::Parallel(0.03),
+        Shock::Ramp {
+            total: 0.02,
+            months: 24,
+        },
+        Shock::Ramp {
+            total: 0.03,
+            months: 36,
+        },
+    ]
+}
+
+/// The outcome of a shock
+#[derive(Debug, Clone)]
+pub struct StressResult {
+    pub shock: Shock,
+    pub worst_term_price: f64,
+    pub total_interest: f64,
+    pub interest_change: f64,
+    pub debt_ratio: f64,
+    pub breached: bool,
+}
+
+impl StressResult {
+    fn new(
+        shock: Shock,
+        worst_term_price: f64,
+        total_interest: f64,
+        base_interest: f64,
+        monthly_income: f64,
+        threshold: f64,
+    ) -> StressResult {
+        let debt_ratio = worst_term_price / monthly_income;
+        StressResult {
+            shock,
+            worst_term_price,
+            total_interest,
+            interest_change: total_interest - base_interest,
+            debt_ratio,
+            breached: debt_ratio > threshold,
+        }
+    }
+}
+
+/// Return an index series moved by a shock starting after a date
+///
+/// The values published up to the date are kept, the shocked series is sampled every month
+/// after it so that ramps are followed by the resets of the loan.
+///
+/// # Arguments
+/// * `index` - the reference rate series
+/// * `shock` - the shock
+/// * `as_of` - the date of the stress test, the shock starts the day after
+/// * `until` - the last date to sample
+pub fn shocked_index(index: &RateIndex, shock: &Shock, as_of: &Date, until: &Date) -> RateIndex {
+    let start = as_of.add_days(1);
+    let mut values: Vec<(Date, f32)> = index
+        .values
+        .iter()
+        .filter(|&&(published, _)| published <= *as_of)
+        .cloned()
+        .collect();
+    values.extend(
+        (0..)
+            .map(|month| (month, start.add_months(month)))
+            .take_while(|&(_, date)| date <= *until)
+            .filter_map(|(month, date)| {
+                index
+                    .rate_at(&date)
+                    .map(|rate| (date, rate + shock.shift_at(month)))
+            }),
+    );
+    RateIndex::new(values)
+}
+
+/// Apply shocks to the index of a variable rate loan
+///
+/// # Arguments
+/// * `loan` - the indexed loan
+/// * `index` - the reference rate series
+/// * `shocks` - the shocks to apply
+/// * `as_of` - the date of the stress test, the index is known up to it
+/// * `monthly_income` - the income of the borrowers by month
+/// * `threshold` - the maximum debt ratio
+pub fn stress_indexed_loan(
+    loan: &IndexedLoan,
+    index: &RateIndex,
+    shocks: &[Shock],
+    as_of: &Date,
+    monthly_income: f64,
+    threshold: f64,
+) -> Result<Vec<StressResult>, HomeCalcError> {
+    check_income(monthly_income)?;
+    let until = loan.due_date(loan.loan.periods());
+    let base_interest = total_interest(loan, index);
+    Ok(shocks
+        .iter()
+        .map(|shock| {
+            let shocked = shocked_index(index, shock, as_of, &until);
+            let installments = loan.installments(&shocked);
+            StressResult::new(
+                *shock,
+                installments
+                    .iter()
+                    .map(|i| i.term_price)
+                    .fold(0_f64, f64::max),
+                installments.iter().map(|i| i.interest).sum(),
+                base_interest,
+                monthly_income,
+                threshold,
+            )
+        }).collect())
+}
+
+fn total_interest(loan: &IndexedLoan, index: &RateIndex) -> f64 {
+    loan.installments(index).iter().map(|i| i.interest).sum()
+}
+
+/// Apply shocks to the loan rate of a planned home purchase
+///
+/// The rate of the loan is not known before signing, so the peak of each shock is applied to
+/// the whole loan. The other loans have a fixed rate, e.g. the loan of a home savings plan, so
+/// they are not shocked but their terms count in the debt ratio.
+///
+/// # Arguments
+/// * `home_invest` - the planned purchase
+/// * `shocks` - the shocks to apply
+/// * `monthly_income` - the income of the borrowers by month
+/// * `threshold` - the maximum debt ratio
+pub fn stress_home_purchase(
+    home_invest: &HomeInvest,
+    shocks: &[Shock],
+    monthly_income: f64,
+    threshold: f64,
+) -> Result<Vec<StressResult>, HomeCalcError> {
+    check_income(monthly_income)?;
+    let loan_for = |shift: f32| {
+        Loan::new(
+            home_invest.years,
+            PERIODICITY,
+            home_invest.loan_rate + shift,
+            home_invest.loan,
+        )
+    };
+    let other_terms = home_invest.other_terms_at(1);
+    let other_interest = home_invest
+        .other_loans
+        .iter()
+        .map(|loan| loan.interest_at(loan.periods()))
+        .sum::<Result<f64, HomeCalcError>>()?;
+    let base = loan_for(0_f32)?;
+    let base_interest = base.interest_at(base.periods())? + other_interest;
+    shocks
+        .iter()
+        .map(|shock| {
+            let loan = loan_for(shock.peak())?;
+            Ok(StressResult::new(
+                *shock,
+                loan.term_price() + other_terms,
+                loan.interest_at(loan.periods())? + other_interest,
+                base_interest,
+                monthly_income,
+                threshold,
+            ))
+        }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use date::date;
+
+    #[test]
+    fn test_shift_at() {
+        let ramp = Shock::Ramp {
+            total: 0.02,
+            months: 24,
+        };
+        assert_eq!(0_f32, ramp.shift_at(0));
+        assert_eq!(0.01, ramp.shift_at(12));
+        assert_eq!(0.02, ramp.shift_at(36));
+        assert_eq!(0.01, Shock::Parallel(0.01).shift_at(0));
+        assert_eq!("+2.00% ramp over 24 months", ramp.to_string());
+        assert_eq!("none", Shock::Parallel(0_f32).to_string());
+    }
+
+    #[test]
+    fn test_stress_indexed_loan() {
+        let loan = Loan::new(20, 12, 0.015_f32, 200_000).unwrap();
+        let start = date(2019, 1, 1);
+        let index = RateIndex::new(vec![(start, 0.005)]);
+        let capped = IndexedLoan::new(loan, start, 0.01, 12, Some(0.01)).unwrap();
+        let shocks = default_shocks();
+        let results =
+            stress_indexed_loan(&capped, &index, &shocks, &start, 4000_f64, 0.35).unwrap();
+        assert_eq!(5, results.len());
+        assert!(results[0].interest_change > 0_f64);
+        assert!(results[0].worst_term_price > capped.loan.term_price());
+        // The cap at +1% makes stronger parallel shocks as bad as the first one
+        assert!((results[2].total_interest - results[0].total_interest).abs() < 0.01);
+        assert!(!results[0].breached);
+    }
+
+    #[test]
+    fn test_shocked_index() {
+        let index = RateIndex::new(vec![(date(2018, 1, 1), 0.005), (date(2019, 1, 1), 0.01)]);
+        let as_of = date(2019, 6, 30);
+        let shocked = shocked_index(&index, &Shock::Parallel(0.02), &as_of, &date(2020, 1, 1));
+        // The history up to the stress date is left as published
+        assert_eq!(Some(0.005), shocked.rate_at(&date(2018, 6, 1)));
+        assert_eq!(Some(0.01), shocked.rate_at(&as_of));
+        assert_eq!(Some(0.03), shocked.rate_at(&date(2019, 7, 1)));
+        assert_eq!(Some(0.03), shocked.rate_at(&date(2020, 1, 1)));
+    }
+
+    #[test]
+    fn test_stress_home_purchase() {
+        let home_invest =
+            HomeInvest::new(43063, 344500, 0.018, 0.125, 0.02, 0.025, 1050, 0.04, 25).unwrap();
+        let results =
+            stress_home_purchase(&home_invest, &[Shock::Parallel(0.01)], 4500_f64, 0.35).unwrap();
+        let shocked = Loan::new(25, 12, 0.018 + 0.01, 344_500).unwrap();
+        assert_eq!(shocked.term_price(), results[0].worst_term_price);
+        assert!(results[0].interest_change > 0_f64);
+        assert!(results[0].breached);
+        let shocks = [Shock::Parallel(0.01)];
+        assert!(stress_home_purchase(&home_invest, &shocks, 0_f64, 0.35).is_err());
+        assert!(stress_home_purchase(&home_invest, &shocks, -4500_f64, 0.35).is_err());
+
+        // The loan of a home savings plan is not shocked but counts in the debt ratio
+        let pel = Loan::new(10, 12, 0.01_f32, 20_000).unwrap();
+        let with_pel = home_invest.with_loan(pel.clone()).unwrap();
+        let with_pel_results = stress_home_purchase(&with_pel, &shocks, 4500_f64, 0.35).unwrap();
+        let worst_term_price = shocked.term_price() + pel.term_price();
+        assert_eq!(worst_term_price, with_pel_results[0].worst_term_price);
+        assert_eq!(worst_term_price / 4500_f64, with_pel_results[0].debt_ratio);
+        assert!((with_pel_results[0].interest_change - results[0].interest_change).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,135 @@
+use error::HomeCalcError;
+use std::fmt;
+use std::str::FromStr;
+
+/// The social charges on investment gains (CSG, CRDS and solidarity levy)
+pub const SOCIAL_CHARGES: f64 = 0.172;
+/// The income tax part of the flat tax (PFU)
+pub const FLAT_INCOME_TAX: f64 = 0.128;
+/// The income tax on assurance-vie gains after 8 years
+pub const ASSURANCE_VIE_INCOME_TAX: f64 = 0.075;
+/// The yearly allowance on assurance-vie gains after 8 years for a single person
+pub const ASSURANCE_VIE_ALLOWANCE: f64 = 4600_f64;
+/// Number of years after which the gains of a PEA are only subject to social charges
+pub const PEA_YEARS: f64 = 5_f64;
+/// Number of years after which the gains of an assurance-vie benefit from the allowance
+pub const ASSURANCE_VIE_YEARS: f64 = 8_f64;
+
+/// The account holding an investment, which sets how its gains are taxed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrapper {
+    /// A securities account, gains are subject to the flat tax of 30%
+    Taxable,
+    /// A Plan d'Épargne en Actions, only social charges are due after 5 years
+    Pea,
+    /// An assurance-vie contract, with a yearly allowance after 8 years (doubled for a couple)
+    AssuranceVie { couple: bool },
+    /// A Livret A, gains are tax free
+    LivretA,
+}
+
+impl Wrapper {
+    /// Return the yearly allowance on the gains, none when the account does not get one
+    ///
+    /// # Arguments
+    /// * `years_held` - number of years since the opening of the account
+    pub fn allowance(&self, years_held: f64) -> f64 {
+        match *self {
+            Wrapper::AssuranceVie { couple } if years_held >= ASSURANCE_VIE_YEARS => {
+                if couple {
+                    2_f64 * ASSURANCE_VIE_ALLOWANCE
+                } else {
+                    ASSURANCE_VIE_ALLOWANCE
+                }
+            }
+            _ => 0_f64,
+        }
+    }
+
+    /// Return the tax due on a gain
+    ///
+    /// The allowance is shared by all the gains withdrawn during a year.
+    ///
+    /// # Arguments
+    /// * `gain` - the gain withdrawn
+    /// * `years_held` - number of years since the opening of the account
+    /// * `allowance_used` - the allowance used by the gains withdrawn earlier in the year
+    ///
+    /// # Example
+    /// ```
+    /// // 10k of gains after 10 years in a PEA: 1720 of social charges
+    /// let tax = Wrapper::Pea.tax_on_gain(10_000_f64, 10_f64, 0_f64);
+    /// ```
+    pub fn tax_on_gain(&self, gain: f64, years_held: f64, allowance_used: f64) -> f64 {
+        if gain <= 0_f64 {
+            return 0_f64;
+        }
+        let flat_tax = gain * (SOCIAL_CHARGES + FLAT_INCOME_TAX);
+        match *self {
+            Wrapper::Taxable => flat_tax,
+            Wrapper::Pea if years_held < PEA_YEARS => flat_tax,
+            Wrapper::Pea => gain * SOCIAL_CHARGES,
+            Wrapper::AssuranceVie { .. } if years_held < ASSURANCE_VIE_YEARS => flat_tax,
+            Wrapper::AssuranceVie { .. } => {
+                let allowance = (self.allowance(years_held) - allowance_used).max(0_f64);
+                gain * SOCIAL_CHARGES + (gain - allowance).max(0_f64) * ASSURANCE_VIE_INCOME_TAX
+            }
+            Wrapper::LivretA => 0_f64,
+        }
+    }
+}
+
+impl fmt::Display for Wrapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Wrapper::Taxable => write!(f, "taxable account"),
+            Wrapper::Pea => write!(f, "PEA"),
+            Wrapper::AssuranceVie { couple: false } => write!(f, "assurance-vie"),
+            Wrapper::AssuranceVie { couple: true } => write!(f, "assurance-vie (couple)"),
+            Wrapper::LivretA => write!(f, "Livret A"),
+        }
+    }
+}
+
+impl FromStr for Wrapper {
+    type Err = HomeCalcError;
+
+    fn from_str(value: &str) -> Result<Wrapper, HomeCalcError> {
+        match value {
+            "taxable" => Ok(Wrapper::Taxable),
+            "pea" => Ok(Wrapper::Pea),
+            "assurance-vie" => Ok(Wrapper::AssuranceVie { couple: false }),
+            "assurance-vie-couple" => Ok(Wrapper::AssuranceVie { couple: true }),
+            "livret-a" => Ok(Wrapper::LivretA),
+            _ => Err(HomeCalcError::InvalidValue {
+                name: "wrapper".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tax_on_gain() {
+        let close = |expected: f64, actual: f64| (expected - actual).abs() < 1e-6;
+        assert!(close(3000_f64, Wrapper::Taxable.tax_on_gain(10_000_f64, 10_f64, 0_f64)));
+        assert!(close(3000_f64, Wrapper::Pea.tax_on_gain(10_000_f64, 4_f64, 0_f64)));
+        assert!(close(1720_f64, Wrapper::Pea.tax_on_gain(10_000_f64, 5_f64, 0_f64)));
+        let single = Wrapper::AssuranceVie { couple: false };
+        let couple = Wrapper::AssuranceVie { couple: true };
+        assert!(close(3000_f64, single.tax_on_gain(10_000_f64, 7_f64, 0_f64)));
+        assert!(close(2125_f64, single.tax_on_gain(10_000_f64, 8_f64, 0_f64)));
+        assert!(close(1720_f64 + 60_f64, couple.tax_on_gain(10_000_f64, 8_f64, 0_f64)));
+        // The allowance left once earlier withdrawals of the year used part of it
+        assert!(close(2350_f64, single.tax_on_gain(10_000_f64, 8_f64, 3000_f64)));
+        assert_eq!(0_f64, single.allowance(7_f64));
+        assert_eq!(0_f64, Wrapper::LivretA.tax_on_gain(10_000_f64, 1_f64, 0_f64));
+        assert_eq!(0_f64, Wrapper::Taxable.tax_on_gain(-100_f64, 1_f64, 0_f64));
+        assert_eq!(Wrapper::Pea, "pea".parse().unwrap());
+        assert!("pee".parse::<Wrapper>().is_err());
+    }
+}
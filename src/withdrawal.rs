@@ -0,0 +1,91 @@
+use error::{check_duration, check_rate, HomeCalcError};
+
+/// The amount taken from an investment each period
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Withdrawal {
+    /// The same amount every period
+    Fixed(u32),
+    /// A ratio of the balance by year
+    Percentage(f32),
+    /// An amount increased every year by the inflation
+    Indexed { amount: u32, inflation: f32 },
+}
+
+/// A drawdown phase of an investment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WithdrawalPlan {
+    pub start: u32,
+    pub withdrawal: Withdrawal,
+}
+
+impl WithdrawalPlan {
+    /// Returns a withdrawal plan, or an error for an invalid parameter
+    ///
+    /// # Arguments
+    /// * `start` - the first period of withdrawal, starting at 1
+    /// * `withdrawal` - the amount taken each period
+    ///
+    /// # Example
+    /// ```
+    /// // 4% of the balance by year from the 11th year
+    /// let plan = WithdrawalPlan::new(121, Withdrawal::Percentage(0.04))?;
+    /// ```
+    pub fn new(start: u32, withdrawal: Withdrawal) -> Result<WithdrawalPlan, HomeCalcError> {
+        check_duration("withdrawal start", start)?;
+        match withdrawal {
+            Withdrawal::Fixed(_) => (),
+            Withdrawal::Percentage(rate) => {
+                check_rate("withdrawal rate", rate)?;
+            }
+            Withdrawal::Indexed { inflation, .. } => {
+                check_rate("withdrawal indexation", inflation)?;
+            }
+        }
+        Ok(WithdrawalPlan { start, withdrawal })
+    }
+
+    /// Return the amount planned for a period, before it is bounded by the balance
+    ///
+    /// # Arguments
+    /// * `periodicity` - number of periods by year
+    /// * `period` - the period, starting at 1
+    /// * `balance` - the balance before the withdrawal
+    pub fn planned_at(&self, periodicity: u8, period: u32, balance: f64) -> f64 {
+        if period < self.start {
+            return 0_f64;
+        }
+        match self.withdrawal {
+            Withdrawal::Fixed(amount) => amount as f64,
+            Withdrawal::Percentage(rate) => balance * rate as f64 / periodicity as f64,
+            Withdrawal::Indexed { amount, inflation } => {
+                let years = (period - self.start) / periodicity as u32;
+                amount as f64 * (1_f64 + inflation as f64).powi(years as i32)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planned_at() {
+        let fixed = WithdrawalPlan::new(13, Withdrawal::Fixed(500)).unwrap();
+        assert_eq!(0_f64, fixed.planned_at(12, 12, 10_000_f64));
+        assert_eq!(500_f64, fixed.planned_at(12, 13, 10_000_f64));
+        let percentage = WithdrawalPlan::new(1, Withdrawal::Percentage(0.06)).unwrap();
+        assert!((percentage.planned_at(12, 1, 10_000_f64) - 50_f64).abs() < 1e-4);
+        let indexed = WithdrawalPlan::new(
+            13,
+            Withdrawal::Indexed {
+                amount: 1000,
+                inflation: 0.02,
+            },
+        ).unwrap();
+        assert_eq!(1000_f64, indexed.planned_at(12, 24, 0_f64));
+        assert!((indexed.planned_at(12, 25, 0_f64) - 1020_f64).abs() < 1e-4);
+        assert!(WithdrawalPlan::new(0, Withdrawal::Fixed(500)).is_err());
+        assert!(WithdrawalPlan::new(1, Withdrawal::Percentage(-0.01)).is_err());
+    }
+}
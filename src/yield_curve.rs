@@ -0,0 +1,198 @@
+use error::HomeCalcError;
+use std::fmt;
+use std::str::FromStr;
+
+/// How the yield of an investment evolves over time, starting from its yield rate
+#[derive(Debug, Clone, PartialEq)]
+pub enum YieldCurve {
+    /// New yields by year `(from period, yield)`, e.g. 3% from the 11th year
+    Phases(Vec<(u32, f32)>),
+    /// A yield moving linearly toward a final yield between two ages of the investor
+    GlidePath {
+        /// Age of the investor at the start of the investment
+        age: u32,
+        /// Age at which the yield starts to decrease
+        start_age: u32,
+        /// Age at which the final yield is reached
+        end_age: u32,
+        /// Yield by year from the end age
+        end_yield: f32,
+    },
+}
+
+fn check_yield(value: f32) -> Result<f32, HomeCalcError> {
+    if !value.is_finite() || value <= -1_f32 {
+        return Err(HomeCalcError::InvalidRate {
+            name: "yield rate of the curve",
+            value,
+        });
+    }
+    Ok(value)
+}
+
+impl YieldCurve {
+    /// Returns a curve of yield phases, or an error for an invalid yield or period
+    ///
+    /// # Arguments
+    /// * `phases` - the new yields by year `(from period, yield)`, in any order
+    ///
+    /// # Example
+    /// ```
+    /// // The initial yield for 10 years, then 4% and 3% from the 16th year
+    /// let curve = YieldCurve::phases(vec![(121, 0.04), (181, 0.03)])?;
+    /// ```
+    pub fn phases(mut phases: Vec<(u32, f32)>) -> Result<YieldCurve, HomeCalcError> {
+        for &(from, yield_rate) in &phases {
+            if from == 0 {
+                return Err(HomeCalcError::InvalidDuration {
+                    name: "yield phase start",
+                    value: from,
+                });
+            }
+            check_yield(yield_rate)?;
+        }
+        phases.sort_by_key(|&(from, _)| from);
+        Ok(YieldCurve::Phases(phases))
+    }
+
+    /// Returns a glide path, or an error when the ages are not in order
+    ///
+    /// # Arguments
+    /// * `age` - age of the investor at the start of the investment
+    /// * `start_age` - age at which the yield starts to decrease
+    /// * `end_age` - age at which the final yield is reached
+    /// * `end_yield` - the final yield by year
+    ///
+    /// # Example
+    /// ```
+    /// // From the initial yield at 50 down to 2% at 65 for an investor of 35
+    /// let curve = YieldCurve::glide_path(35, 50, 65, 0.02)?;
+    /// ```
+    pub fn glide_path(
+        age: u32,
+        start_age: u32,
+        end_age: u32,
+        end_yield: f32,
+    ) -> Result<YieldCurve, HomeCalcError> {
+        if start_age > end_age {
+            return Err(HomeCalcError::InvalidValue {
+                name: "glide path ages".to_string(),
+                value: format!("{} to {}", start_age, end_age),
+            });
+        }
+        Ok(YieldCurve::GlidePath {
+            age,
+            start_age,
+            end_age,
+            end_yield: check_yield(end_yield)?,
+        })
+    }
+
+    /// Return the yield by year of a period
+    ///
+    /// # Arguments
+    /// * `initial` - the yield by year at the start of the investment
+    /// * `periodicity` - number of periods by year
+    /// * `period` - the period, starting at 1
+    pub fn yield_at(&self, initial: f32, periodicity: u8, period: u32) -> f32 {
+        match *self {
+            YieldCurve::Phases(ref phases) => phases
+                .iter()
+                .take_while(|&&(from, _)| from <= period)
+                .last()
+                .map_or(initial, |&(_, yield_rate)| yield_rate),
+            YieldCurve::GlidePath {
+                age,
+                start_age,
+                end_age,
+                end_yield,
+            } => {
+                let age = age as f32 + period.saturating_sub(1) as f32 / periodicity as f32;
+                if age <= start_age as f32 {
+                    initial
+                } else if age >= end_age as f32 {
+                    end_yield
+                } else {
+                    let progress = (age - start_age as f32) / (end_age - start_age) as f32;
+                    initial + (end_yield - initial) * progress
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for YieldCurve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            YieldCurve::Phases(ref phases) => {
+                let phases: Vec<String> = phases
+                    .iter()
+                    .map(|&(from, yield_rate)| {
+                        format!("{}% from period {}", yield_rate * 100_f32, from)
+                    }).collect();
+                write!(f, "{}", phases.join(", "))
+            }
+            YieldCurve::GlidePath {
+                age,
+                start_age,
+                end_age,
+                end_yield,
+            } => write!(
+                f,
+                "glide path toward {}% from {} to {} years old, starting at {}",
+                end_yield * 100_f32,
+                start_age,
+                end_age,
+                age
+            ),
+        }
+    }
+}
+
+impl FromStr for YieldCurve {
+    type Err = HomeCalcError;
+
+    /// Parse a glide path from `age:start-age:end-age:end-yield`, the yield being in percent
+    fn from_str(value: &str) -> Result<YieldCurve, HomeCalcError> {
+        let invalid = || HomeCalcError::InvalidValue {
+            name: "glide path".to_string(),
+            value: value.to_string(),
+        };
+        let fields: Vec<&str> = value.split(':').map(str::trim).collect();
+        if fields.len() != 4 {
+            return Err(invalid());
+        }
+        let ages: Vec<u32> = fields[..3]
+            .iter()
+            .map(|field| field.parse().map_err(|_| invalid()))
+            .collect::<Result<_, _>>()?;
+        let end_yield: f32 = fields[3].parse().map_err(|_| invalid())?;
+        YieldCurve::glide_path(ages[0], ages[1], ages[2], end_yield / 100_f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases() {
+        let curve = YieldCurve::phases(vec![(181, 0.03), (121, 0.04)]).unwrap();
+        assert_eq!(0.06, curve.yield_at(0.06, 12, 120));
+        assert_eq!(0.04, curve.yield_at(0.06, 12, 121));
+        assert_eq!(0.03, curve.yield_at(0.06, 12, 500));
+        assert!(YieldCurve::phases(vec![(0, 0.03)]).is_err());
+        assert!(YieldCurve::phases(vec![(12, -1.5)]).is_err());
+    }
+
+    #[test]
+    fn test_glide_path() {
+        let curve: YieldCurve = "40:50:60:2".parse().unwrap();
+        assert_eq!(0.06, curve.yield_at(0.06, 12, 120));
+        // Halfway at 55 years old
+        assert!((curve.yield_at(0.06, 12, 181) - 0.04).abs() < 1e-6);
+        assert_eq!(0.02, curve.yield_at(0.06, 12, 241));
+        assert!("40:60:50:2".parse::<YieldCurve>().is_err());
+        assert!("40:50:2".parse::<YieldCurve>().is_err());
+    }
+}